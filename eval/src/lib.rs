@@ -1,9 +1,42 @@
-#[derive(Debug, Clone, serde_derive::Deserialize, serde_derive::Serialize, PartialEq)]
+pub mod plot;
+pub mod report;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::Result;
+use anyhow::bail;
+use csv::StringRecord;
+use lib::weights::WeightFn;
+
+/// a tau of a case: one of the variants on the input, or one of the bounds on
+/// the arbitrations the solver found. (there's no tau_w: it has no
+/// denominator yet, see `lib::tau_w::TauVariants`.)
+///
+/// ```
+/// # use eval::Measure;
+/// assert_eq!("max".parse::<Measure>().unwrap(), Measure::Max);
+/// assert!("w".parse::<Measure>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    A,
+    B,
+    Max,
+    Min,
+}
+
+/// a row of output. its taus are named by [`tau_columns`], and go between the
+/// input and the rest.
+#[derive(Debug, Clone, PartialEq)]
 pub struct OutCsvRow {
-    pub t_a: f64,
-    pub t_b: f64,
-    pub t_max: f64,
-    pub t_min: f64,
+    /// the input, so a run can be continued
+    pub a: String,
+    pub b: String,
+    /// how the run of the solver ended (see [`lib::RunStatus::name`]). the
+    /// taus of a case that went over the limits are NaN.
+    pub status: &'static str,
+    pub taus: Vec<f64>,
     pub length: usize,
     // pub frac_ties_x: f64,
     // pub frac_ties_y: f64,
@@ -13,4 +46,120 @@ pub struct OutCsvRow {
     pub longest_tie: usize,
     pub permutation_count: u128,
     pub compute_time: f32,
+    /// user + system time of the solver, in seconds
+    pub cpu_time: f32,
+    /// peak resident set size of the solver, in KiB
+    pub peak_rss: u64,
+}
+
+/// the names of the tau columns: `t_<measure>` for every measure of every
+/// weight, in that order. with more than one weight they end in `_<weight>`.
+///
+/// ```
+/// # use eval::Measure;
+/// # use eval::tau_columns;
+/// # use lib::weights::ap_weight;
+/// # use lib::weights::WeightFn;
+/// # use lib::weights::unweighted;
+/// let measures = [Measure::B, Measure::Max];
+/// let one = tau_columns(&[("ap_weight".to_string(), ap_weight)], &measures);
+/// assert_eq!(one, vec!["t_b", "t_max"]);
+/// let two: [(String, WeightFn); 2] = [("ap".into(), ap_weight), ("u".into(), unweighted)];
+/// assert_eq!(tau_columns(&two, &measures), vec!["t_b_ap", "t_max_ap", "t_b_u", "t_max_u"]);
+/// ```
+pub fn tau_columns(weights: &[(String, WeightFn)], measures: &[Measure]) -> Vec<String> {
+    weights
+        .iter()
+        .flat_map(|(name, _)| {
+            measures.iter().map(move |m| match weights.len() {
+                1 => format!("t_{m}"),
+                _ => format!("t_{m}_{name}"),
+            })
+        })
+        .collect()
+}
+
+/// is `record`, with `header`, a case the solver finished? outputs from before
+/// there was a status column only have those.
+///
+/// ```
+/// # use csv::StringRecord;
+/// # use eval::is_finished;
+/// let header = StringRecord::from(vec!["a", "b", "status"]);
+/// assert!(is_finished(&header, &StringRecord::from(vec!["x y", "y x", "done"])));
+/// assert!(!is_finished(&header, &StringRecord::from(vec!["x y", "y x", "timeout"])));
+/// assert!(is_finished(&StringRecord::from(vec!["a", "b"]), &StringRecord::from(vec!["x y", "y x"])));
+/// ```
+pub fn is_finished(header: &StringRecord, record: &StringRecord) -> bool {
+    header
+        .iter()
+        .position(|h| h == "status")
+        .is_none_or(|i| &record[i] == "done")
+}
+
+impl OutCsvRow {
+    /// the header of the output, with these tau columns
+    pub fn header(tau_columns: &[String]) -> Vec<String> {
+        let mut header = vec!["a".to_string(), "b".to_string(), "status".to_string()];
+        header.extend(tau_columns.iter().cloned());
+        header.extend(
+            [
+                "length",
+                "frac_ties",
+                "sum_of_tie_lengths",
+                "tie_count",
+                "longest_tie",
+                "permutation_count",
+                "compute_time",
+                "cpu_time",
+                "peak_rss",
+            ]
+            .map(String::from),
+        );
+        header
+    }
+
+    /// the row in the order of [`OutCsvRow::header`]
+    pub fn record(&self) -> Vec<String> {
+        let mut record = vec![self.a.clone(), self.b.clone(), self.status.to_string()];
+        record.extend(self.taus.iter().map(|t| format!("{t:?}")));
+        record.extend([
+            self.length.to_string(),
+            format!("{:?}", self.frac_ties),
+            self.sum_of_tie_lengths.to_string(),
+            self.tie_count.to_string(),
+            self.longest_tie.to_string(),
+            self.permutation_count.to_string(),
+            format!("{:?}", self.compute_time),
+            format!("{:?}", self.cpu_time),
+            self.peak_rss.to_string(),
+        ]);
+        record
+    }
+}
+
+impl FromStr for Measure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "a" => Measure::A,
+            "b" => Measure::B,
+            "max" => Measure::Max,
+            "min" => Measure::Min,
+            _ => bail!("unknown measure {s:?}, there are a, b, max and min"),
+        })
+    }
+}
+
+impl Display for Measure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Measure::A => "a",
+            Measure::B => "b",
+            Measure::Max => "max",
+            Measure::Min => "min",
+        };
+        write!(f, "{name}")
+    }
 }
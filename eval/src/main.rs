@@ -1,97 +1,162 @@
 use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
 
 use anyhow::Result;
 use anyhow::anyhow;
+use anyhow::ensure;
 use clap::Parser;
 use clap_derive::Parser;
+use csv::ReaderBuilder;
 use csv::StringRecord;
 use csv::Writer;
+use eval::Measure;
 use eval::OutCsvRow;
+use eval::tau_columns;
 use indicatif::ParallelProgressIterator;
 use lib::AlgoOut;
 use lib::CHUNK_SIZE;
+use lib::Limits;
 use lib::PRECISION;
 use lib::RankingsCsvRow;
+use lib::RunStatus;
+use lib::Usage;
+use lib::cache::Cache;
+use lib::cache::Cached;
+use lib::cache::solver_id;
+use lib::canon::dedup_rows;
+use lib::def::Bound;
 use lib::def::Element;
+use lib::def::PartialOrder;
 use lib::def::Ranking;
 use lib::def::partial_from_string;
 use lib::def::strict_from_partial;
+use lib::expr::expression_weight;
 use lib::progress_bar;
 use lib::read_glob_csv;
+use lib::resume_csv;
 use lib::run_solver_on;
+use lib::shard::Shard;
+use lib::shard::output_path;
+use lib::stream::checkpoint;
+use lib::stream::count_rows;
+use lib::stream::pipeline;
+use lib::stream::resume_stream;
+use lib::stream::stream_rows;
 use lib::tau_w::TauVariants;
-use lib::tau_w::tau_partial;
-use lib::tau_w::tau_w;
-use lib::weights::ap_weight;
+use lib::tau_w::tau_auto;
+use lib::tau_w::tau_partial_auto;
+use lib::weights::WeightFn;
+use lib::weights::by_name;
+use lib::weights::is_unweighted;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
+use solver::bounds::bf::tau_bounds_bf;
+use solver::bounds::find_tau_bounds;
 use verifier::verify::parse_algo_sol;
 
+const HEADER: &[&str] = &["a", "b"];
+
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
     pub solver: PathBuf,
     pub output: PathBuf,
     pub data: String,
+    #[command(flatten)]
+    pub limits: Limits,
+    /// only run slice i of n (from 1) of the cases, see `lib::shard`
+    #[arg(long)]
+    pub shard: Option<Shard>,
+    /// read the input a chunk at a time, in its order, instead of all at once
+    /// (see `lib::stream`). can't be sharded
+    #[arg(long, conflicts_with = "shard")]
+    pub stream: bool,
+    /// start over instead of continuing the rows already in `output`
+    #[arg(long)]
+    pub overwrite: bool,
+    /// keep results in (and take them from) this directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+    /// a weight to compute the taus with, by name or as an expression, once per
+    /// weight. the first one has to be the one the solver was built with: it's
+    /// the one the bounds are checked with. the bounds of the others are found
+    /// exactly in this process, by brute force unless they're unweighted, and
+    /// are NaN where there are too many arbitrations for that. at most one of
+    /// them can be an expression (see `lib::expr::expression_weight`)
+    #[arg(long = "weight", default_value = "ap_weight")]
+    pub weights: Vec<String>,
+    /// the taus to compute with every weight: the variants a and b of the
+    /// input, and the max and min bounds of the solution
+    #[arg(long, value_delimiter = ',', default_value = "a,b,max,min")]
+    pub measures: Vec<Measure>,
 }
 
+/// the solvers are built with their weight, so their hash already says which
+/// one it is
+const WEIGHT: &str = "builtin";
+
 fn main() -> Result<()> {
     let args = Cli::parse();
 
     let start = Instant::now();
 
-    let rows = read_glob_csv(&args.data, vec!["a", "b"])?;
-
-    let mut writer = Writer::from_path(&args.output)?;
-
-    let mut cases = rows
-        .par_iter()
-        .map(parse_row)
-        .collect::<Result<Vec<RankingsCsvRow>>>()?;
-
-    println!(
-        "loading {} test cases ({}s)",
-        cases.len(),
-        start.elapsed().as_secs_f32()
-    );
-    cases.dedup_by(|r1, r2| {
-        let mut map = BTreeMap::new();
-        let p1 = partial_from_string(&r1.a, &mut map).unwrap();
-        let p2 = partial_from_string(&r1.b, &mut map).unwrap();
-        let mut map = BTreeMap::new();
-        let p3 = partial_from_string(&r2.a, &mut map).unwrap();
-        let p4 = partial_from_string(&r2.b, &mut map).unwrap();
-        p1.rank_eq(&p3) && p2.rank_eq(&p4)
-    });
-    cases.sort_unstable_by_key(|row| row.a.len());
-
-    let num_tests = cases.len();
-    println!(
-        "parsed {} into {num_tests} lines of input in {}s",
-        rows.len(),
-        start.elapsed().as_secs_f32()
+    let expressions = args.weights.iter().filter(|name| by_name(name).is_err());
+    ensure!(
+        expressions.count() <= 1,
+        "only one of the weights can be an expression"
     );
+    let weights = args
+        .weights
+        .iter()
+        .map(|name| {
+            let w = by_name(name).or_else(|_| expression_weight(name))?;
+            Ok((name.clone(), w))
+        })
+        .collect::<Result<Vec<(String, WeightFn)>>>()?;
+    let header = OutCsvRow::header(&tau_columns(&weights, &args.measures));
 
-    let pb = progress_bar(num_tests as u64)?;
+    // carry on where an earlier run stopped
+    if let Some(shard) = args.shard {
+        shard.start(&args.output)?;
+    }
+    let output = output_path(&args.output, args.shard);
+    let (mut writer, done, skip) = if args.stream {
+        let (writer, skip) = resume_stream(&output, args.overwrite)?;
+        (writer, HashSet::new(), skip)
+    } else {
+        let (writer, done) = resume_csv(&output, args.overwrite)?;
+        (writer, done, 0)
+    };
+    start_output(&output, &mut writer, &header)?;
 
-    cases.chunks(CHUNK_SIZE * 2).try_for_each(|group| {
-        let outputs = group
+    let cache = args.cache.as_deref().map(Cache::open).transpose()?;
+    let solver = solver_id(&args.solver)?;
+    let cache = cache.as_ref().map(|c| (c, solver.as_str()));
+
+    let pb = progress_bar(0)?;
+    let mut unfinished = 0;
+    let mut solve = |group: &[RankingsCsvRow]| {
+        let runs = group
             .into_par_iter()
-            .map(|c| run_solver_on(&args.solver, &c).map(|x| (x, c)))
+            .map(|c| run_cached(&args.solver, c, args.limits, cache).map(|x| (x, c)))
             .progress_with(pb.clone())
             .collect::<Result<Vec<_>>>()
-            .map_err(|e| anyhow!("runner err: {e:?}"))?
-            .into_par_iter()
-            .map(|((x, dt), c)| parse_algo_sol(x).map(|x| x.map(|xx| (xx, c, dt))))
-            .collect::<Result<Vec<_>>>()
-            .map_err(|e| anyhow!("parser err: {e:?}"))?
-            .into_iter()
-            .flatten()
-            .map(map_to_out)
+            .map_err(|e| anyhow!("runner err: {e:?}"))?;
+
+        let outputs = runs
+            .iter()
+            .map(|(run, c)| {
+                if let Run::OverLimits(..) = run {
+                    unfinished += 1;
+                }
+                map_to_out(run, c, &weights, &args.measures)
+            })
             .collect::<Result<Vec<_>>>()?
             .into_iter()
             .flatten()
@@ -99,57 +164,150 @@ fn main() -> Result<()> {
 
         outputs
             .into_iter()
-            .try_for_each(|o| writer.serialize(o))
-            .map_err(|e| anyhow!("writer err: {e:?}"))
-    })?;
+            .try_for_each(|o| writer.write_record(o.record()))
+            .map_err(|e| anyhow!("writer err: {e:?}"))?;
+        // so a killed run loses at most this group
+        writer.flush()?;
+        Ok::<(), anyhow::Error>(())
+    };
 
+    let num_tests = if args.stream {
+        let total = count_rows(&args.data, HEADER)?;
+        println!("streaming {total} lines of input ({skip} already done)");
+        pb.set_length((total - skip) as u64);
+        let chunks = stream_rows(&args.data, HEADER, skip..total, CHUNK_SIZE * 2)?;
+        let mut rows = skip;
+        pipeline(chunks, 2, |group| {
+            rows += group.len();
+            solve(&dedup_rows(group)?)?;
+            checkpoint(&output, rows)
+        })?;
+        total
+    } else {
+        let rows = read_glob_csv(&args.data, HEADER.to_vec())?;
+
+        let cases = rows
+            .par_iter()
+            .map(parse_row)
+            .collect::<Result<Vec<RankingsCsvRow>>>()?;
+
+        println!(
+            "loading {} test cases ({}s)",
+            cases.len(),
+            start.elapsed().as_secs_f32()
+        );
+        // the same input under other names only needs solving once
+        let mut cases = dedup_rows(cases)?;
+        cases.sort_unstable_by_key(|row| row.a.len());
+        if let Some(shard) = args.shard {
+            shard.select(&mut cases);
+        }
+        let num_tests = cases.len();
+        cases.retain(|c| !done.contains(c));
+
+        println!(
+            "parsed {} into {num_tests} lines of input ({} already done) in {}s",
+            rows.len(),
+            done.len(),
+            start.elapsed().as_secs_f32()
+        );
+        pb.set_length(cases.len() as u64);
+        cases.chunks(CHUNK_SIZE * 2).try_for_each(&mut solve)?;
+        num_tests
+    };
+
+    if let Some(shard) = args.shard {
+        shard.finish(&args.output, num_tests, unfinished)?;
+    }
     println!("{num_tests} done in {}s", start.elapsed().as_secs_f32());
+    if unfinished > 0 {
+        println!("{unfinished} went over the limits");
+    }
     Ok(())
 }
 
-fn map_to_out(xc: (AlgoOut, &RankingsCsvRow, Duration)) -> Result<Option<OutCsvRow>> {
-    let mut inp_map: BTreeMap<String, Element> = BTreeMap::new();
-    let rank_a = partial_from_string(&xc.1.a, &mut inp_map)?;
-    let rank_b = partial_from_string(&xc.1.b, &mut inp_map)?;
-
-    let mut sol_inp_map: BTreeMap<String, Element> = BTreeMap::new();
-    let sol_str_a = partial_from_string(&xc.0.maxp[0].0, &mut sol_inp_map)?;
-    let sol_str_b = partial_from_string(&xc.0.maxp[0].1, &mut sol_inp_map)?;
-    let p_max_a = strict_from_partial(&sol_str_a)?;
-    let p_max_b = strict_from_partial(&sol_str_b)?;
-
-    let mut sol_inp_map: BTreeMap<String, Element> = BTreeMap::new();
-    let sol_str_a = partial_from_string(&xc.0.minp[0].0, &mut sol_inp_map)?;
-    let sol_str_b = partial_from_string(&xc.0.minp[0].1, &mut sol_inp_map)?;
-    let p_min_a = strict_from_partial(&sol_str_a)?;
-    let p_min_b = strict_from_partial(&sol_str_b)?;
+/// how a run of the solver on a case went
+enum Run {
+    Solved(Cached),
+    /// the status says which limit it was
+    OverLimits(RunStatus, Duration, Usage),
+}
 
-    let t_a = tau_partial(&rank_a, &rank_b, ap_weight, TauVariants::A)?;
-    let t_b = tau_partial(&rank_a, &rank_b, ap_weight, TauVariants::B)?;
+/// the result of `solver` on `c`, from the cache if it's there. going over the
+/// limits isn't kept in the cache, since other limits might not.
+fn run_cached(
+    solver: &PathBuf,
+    c: &RankingsCsvRow,
+    limits: Limits,
+    cache: Option<(&Cache, &str)>,
+) -> Result<Run> {
+    if let Some((cache, id)) = cache
+        && let Some(hit) = cache.get(c, WEIGHT, id)?
+    {
+        return Ok(Run::Solved(hit));
+    }
+    let (status, time, usage) = run_solver_on(solver, &c, limits)?;
+    let out = match status {
+        RunStatus::Done(x) => {
+            parse_algo_sol(x).map_err(|e| anyhow!("parser err: {e:?}"))?
+        }
+        RunStatus::Timeout | RunStatus::ResourceExceeded => {
+            return Ok(Run::OverLimits(status, time, usage));
+        }
+    };
+    let result = Cached { out, time, usage };
+    if let Some((cache, id)) = cache {
+        cache.put(c, WEIGHT, id, &result)?;
+    }
+    Ok(Run::Solved(result))
+}
 
-    if t_b.is_nan() {
-        return Ok(None);
+/// the header of a new output, or the check that a continued one has it
+fn start_output(path: &Path, writer: &mut Writer<File>, header: &[String]) -> Result<()> {
+    if std::fs::metadata(path)?.len() == 0 {
+        writer.write_record(header)?;
+        return Ok(());
     }
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let found = reader.headers()?;
+    ensure!(
+        found.iter().eq(header.iter()),
+        "{} has other columns (other weights or measures?), see --overwrite",
+        path.display()
+    );
+    Ok(())
+}
 
-    let t_max = tau_w(&p_max_a, &p_max_b, ap_weight)?;
-    let t_min = tau_w(&p_min_a, &p_min_b, ap_weight)?;
+/// the row of case `c`. cases the solver skipped, or whose tau_b is undefined,
+/// get none. the ones that went over the limits get one without taus, so a
+/// continued run doesn't try them again.
+fn map_to_out(
+    run: &Run,
+    c: &RankingsCsvRow,
+    weights: &[(String, WeightFn)],
+    measures: &[Measure],
+) -> Result<Option<OutCsvRow>> {
+    let mut inp_map: BTreeMap<String, Element> = BTreeMap::new();
+    let rank_a = partial_from_string(&c.a, &mut inp_map)?;
+    let rank_b = partial_from_string(&c.b, &mut inp_map)?;
 
-    assert!(
-        t_min - PRECISION < t_b,
-        "error in: \"{}\" \"{}\"\ntb:{t_b}\ntmin:{t_min}\npmin:{}/{}",
-        &xc.1.a,
-        &xc.1.b,
-        &xc.0.minp[0].0,
-        &xc.0.minp[0].1,
-    );
-    assert!(
-        t_max + PRECISION > t_b,
-        "error in: \"{}\" \"{}\"\ntb:{t_b}\ntmax:{t_max}\npmax:{}/{}",
-        &xc.1.a,
-        &xc.1.b,
-        &xc.0.minp[0].0,
-        &xc.0.minp[0].1,
-    );
+    let (taus, status, time, usage) = match run {
+        Run::Solved(Cached {
+            out: Some(out),
+            time,
+            usage,
+        }) => match taus(&rank_a, &rank_b, out, c, weights, measures)? {
+            Some(taus) => (taus, "done", *time, *usage),
+            None => return Ok(None),
+        },
+        Run::Solved(Cached { out: None, .. }) => return Ok(None),
+        Run::OverLimits(status, time, usage) => (
+            vec![f64::NAN; weights.len() * measures.len()],
+            status.name(),
+            *time,
+            *usage,
+        ),
+    };
 
     let tie_count = rank_a
         .iter()
@@ -167,10 +325,10 @@ fn map_to_out(xc: (AlgoOut, &RankingsCsvRow, Duration)) -> Result<Option<OutCsvR
     let length = rank_a.set_size();
 
     Ok(Some(OutCsvRow {
-        t_a,
-        t_b,
-        t_max,
-        t_min,
+        a: c.a.clone(),
+        b: c.b.clone(),
+        status,
+        taus,
         length,
         frac_ties: items_in_ties as f64 / (2.0 * length as f64),
         tie_count,
@@ -189,10 +347,113 @@ fn map_to_out(xc: (AlgoOut, &RankingsCsvRow, Duration)) -> Result<Option<OutCsvR
         permutation_count: rank_a
             .linear_ext_count()
             .saturating_mul(rank_b.linear_ext_count()),
-        compute_time: xc.2.as_secs_f32(),
+        compute_time: time.as_secs_f32(),
+        cpu_time: usage.cpu.as_secs_f32(),
+        peak_rss: usage.max_rss,
     }))
 }
 
+/// the taus of every measure of every weight, for the solution `out` of `c`.
+/// `None` if tau_b is undefined.
+fn taus(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    out: &AlgoOut,
+    c: &RankingsCsvRow,
+    weights: &[(String, WeightFn)],
+    measures: &[Measure],
+) -> Result<Option<Vec<f64>>> {
+    let mut sol_inp_map: BTreeMap<String, Element> = BTreeMap::new();
+    let sol_str_a = partial_from_string(&out.maxp[0].0, &mut sol_inp_map)?;
+    let sol_str_b = partial_from_string(&out.maxp[0].1, &mut sol_inp_map)?;
+    let p_max_a = strict_from_partial(&sol_str_a)?;
+    let p_max_b = strict_from_partial(&sol_str_b)?;
+
+    let mut sol_inp_map: BTreeMap<String, Element> = BTreeMap::new();
+    let sol_str_a = partial_from_string(&out.minp[0].0, &mut sol_inp_map)?;
+    let sol_str_b = partial_from_string(&out.minp[0].1, &mut sol_inp_map)?;
+    let p_min_a = strict_from_partial(&sol_str_a)?;
+    let p_min_b = strict_from_partial(&sol_str_b)?;
+
+    // the solver only found the bounds of its own weight, which is the first
+    let (_, w) = weights[0];
+    let t_b = tau_partial_auto(rank_a, rank_b, w, TauVariants::B)?;
+
+    if t_b.is_nan() {
+        return Ok(None);
+    }
+
+    let t_max = tau_auto(&p_max_a, &p_max_b, w)?;
+    let t_min = tau_auto(&p_min_a, &p_min_b, w)?;
+
+    assert!(
+        t_min - PRECISION < t_b,
+        "error in: \"{}\" \"{}\"\ntb:{t_b}\ntmin:{t_min}\npmin:{}/{}",
+        &c.a,
+        &c.b,
+        &out.minp[0].0,
+        &out.minp[0].1,
+    );
+    assert!(
+        t_max + PRECISION > t_b,
+        "error in: \"{}\" \"{}\"\ntb:{t_b}\ntmax:{t_max}\npmax:{}/{}",
+        &c.a,
+        &c.b,
+        &out.minp[0].0,
+        &out.minp[0].1,
+    );
+
+    // the arbitrations of the solver are only optimal for its weight, so the
+    // bounds of the others are solved for here, if they're asked for
+    let bounds = std::iter::once(Ok((t_min, t_max)))
+        .chain(weights[1..].iter().map(|(_, w)| {
+            if !measures
+                .iter()
+                .any(|m| matches!(m, Measure::Max | Measure::Min))
+            {
+                return Ok((f64::NAN, f64::NAN));
+            }
+            exact_bounds(rank_a, rank_b, *w)
+        }))
+        .collect::<Result<Vec<(f64, f64)>>>()?;
+
+    let taus = weights
+        .iter()
+        .zip(bounds)
+        .flat_map(|((_, w), bounds)| measures.iter().map(move |m| (*w, bounds, *m)))
+        .map(|(w, (t_min, t_max), m)| match m {
+            Measure::A => tau_partial_auto(rank_a, rank_b, w, TauVariants::A),
+            Measure::B => tau_partial_auto(rank_a, rank_b, w, TauVariants::B),
+            Measure::Max => Ok(t_max),
+            Measure::Min => Ok(t_min),
+        })
+        .collect::<Result<Vec<f64>>>()?;
+    Ok(Some(taus))
+}
+
+/// the (tmin, tmax) of `w`, exactly: the graph algorithm is only exact for some
+/// weights, so only the unweighted ones are sorted, and the rest are brute
+/// forced. NaN if there are too many arbitrations for that.
+fn exact_bounds(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    w: WeightFn,
+) -> Result<(f64, f64)> {
+    let bounds = if is_unweighted(w) {
+        find_tau_bounds(rank_a, rank_b, w)?
+    } else {
+        match tau_bounds_bf(rank_a, rank_b, |x, y| tau_auto(x, y, w)) {
+            Ok(bounds) => bounds,
+            Err(e) if e.to_string().starts_with("skipped") => {
+                return Ok((f64::NAN, f64::NAN));
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    let t = |b: Option<Bound>| b.map_or(f64::NAN, |b| b.t);
+    Ok((t(bounds.lb), t(bounds.ub)))
+}
+
 pub fn parse_row(row: &StringRecord) -> Result<RankingsCsvRow> {
     let p = row.deserialize(None)?;
     Ok(p)
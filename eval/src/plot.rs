@@ -0,0 +1,485 @@
+//! the figures of `plots/*.R`, without R
+//!
+//! a [`Heat`] is a 2d histogram of two columns of `eval` output, such as `t_b`
+//! against `t_min`, and a [`Dist`] is the histogram of one column for the cases
+//! where other columns are over a threshold. they're drawn to svg or png,
+//! depending on where they go.
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use csv::StringRecord;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use lib::open_csv;
+
+use crate::is_finished;
+
+/// the rows of `eval` outputs, by the name of their columns
+pub struct Table {
+    pub header: StringRecord,
+    pub rows: Vec<StringRecord>,
+}
+
+/// a 2d histogram of `y` against `x`
+#[derive(Debug, Clone)]
+pub struct Heat {
+    pub x: String,
+    pub y: String,
+    pub bins: usize,
+    pub title: String,
+    /// how opaque the `y = x` line is, if it's drawn
+    pub diagonal: Option<f64>,
+}
+
+/// histograms of `of`, one for the cases of each of `by` (the first of them
+/// that's at least `above`), on top of each other
+#[derive(Debug, Clone)]
+pub struct Dist {
+    pub of: String,
+    pub by: Vec<String>,
+    pub above: f64,
+    pub bins: usize,
+    pub title: String,
+    /// where dashed vertical lines go
+    pub lines: Vec<f64>,
+}
+
+/// the colours of the groups of a [`Dist`], in order
+const DIST_COLOURS: [RGBColor; 4] = [
+    RGBColor(0xd0, 0x2f, 0x42),
+    RGBColor(0x48, 0x10, 0xa0),
+    RGBColor(0x20, 0x90, 0x60),
+    RGBColor(0xe0, 0x90, 0x20),
+];
+
+/// a few points of the magma colour map, which the rest is interpolated from
+const MAGMA: [(u8, u8, u8); 9] = [
+    (0x00, 0x00, 0x04),
+    (0x1c, 0x10, 0x44),
+    (0x4f, 0x12, 0x7b),
+    (0x81, 0x25, 0x81),
+    (0xb5, 0x36, 0x7a),
+    (0xe5, 0x50, 0x64),
+    (0xfb, 0x87, 0x61),
+    (0xfe, 0xc2, 0x87),
+    (0xfc, 0xfd, 0xbf),
+];
+
+/// the font every figure is in
+const FONT: &str = "sans-serif";
+
+impl Table {
+    /// the rows of every csv file matching `g`, which all have the columns of
+    /// the first. the cases that went over the limits are left out.
+    pub fn read(g: &str) -> Result<Table> {
+        let mut header = None;
+        let mut rows = Vec::new();
+        for path in glob::glob(g)? {
+            let path = path?;
+            let mut reader = open_csv(&path, &[])?;
+            let h = reader.headers()?.clone();
+            match &header {
+                None => header = Some(h.clone()),
+                Some(first) if *first != h => {
+                    bail!("{} has other columns than the first file", path.display())
+                }
+                Some(_) => {}
+            }
+            for record in reader.records() {
+                let record = record?;
+                if is_finished(&h, &record) {
+                    rows.push(record);
+                }
+            }
+        }
+        let header = header.ok_or_else(|| anyhow!("no files match {g}"))?;
+        Ok(Table { header, rows })
+    }
+
+    /// the values of a column. besides the ones in the file there's `width`,
+    /// `t_max - t_min`, and `scaled_longest_tie`, `longest_tie / length`.
+    ///
+    /// ```
+    /// # use csv::StringRecord;
+    /// # use eval::plot::Table;
+    /// let table = Table {
+    ///     header: StringRecord::from(vec!["t_max", "t_min"]),
+    ///     rows: vec![StringRecord::from(vec!["1.0", "0.5"])],
+    /// };
+    /// assert_eq!(table.column("t_min").unwrap(), vec![0.5]);
+    /// assert_eq!(table.column("width").unwrap(), vec![0.5]);
+    /// assert!(table.column("t_a").is_err());
+    /// ```
+    pub fn column(&self, name: &str) -> Result<Vec<f64>> {
+        match name {
+            "width" => Ok(zip_with(
+                self.column("t_max")?,
+                self.column("t_min")?,
+                |max, min| max - min,
+            )),
+            "scaled_longest_tie" => Ok(zip_with(
+                self.column("longest_tie")?,
+                self.column("length")?,
+                |tie, len| tie / len,
+            )),
+            _ => {
+                let i = self
+                    .header
+                    .iter()
+                    .position(|h| h == name)
+                    .ok_or_else(|| anyhow!("there's no {name:?} column"))?;
+                self.rows
+                    .iter()
+                    .map(|r| {
+                        r[i].parse::<f64>()
+                            .map_err(|e| anyhow!("bad {name} {:?}: {e}", &r[i]))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn zip_with(a: Vec<f64>, b: Vec<f64>, f: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+    a.into_iter().zip(b).map(|(a, b)| f(a, b)).collect()
+}
+
+/// the range of the axis of a column: taus go from -1 to 1, the rest from
+/// their smallest to their largest value
+pub fn axis_range(name: &str, values: &[f64]) -> (f64, f64) {
+    match name {
+        _ if name.starts_with("t_") => (-1.0, 1.0),
+        "width" => (0.0, 2.0),
+        _ => {
+            let finite = values.iter().copied().filter(|v| v.is_finite());
+            let min = finite.clone().fold(f64::INFINITY, f64::min);
+            let max = finite.fold(f64::NEG_INFINITY, f64::max);
+            match (min.is_finite(), max > min) {
+                (true, true) => (min, max),
+                (true, false) => (min - 0.5, min + 0.5),
+                _ => (0.0, 1.0),
+            }
+        }
+    }
+}
+
+/// how the axis of a column is labelled
+///
+/// ```
+/// # use eval::plot::axis_label;
+/// assert_eq!(axis_label("t_min"), "τ-min");
+/// assert_eq!(axis_label("t_b_ap"), "τ-b (ap)");
+/// assert_eq!(axis_label("frac_ties"), "tie fraction");
+/// ```
+pub fn axis_label(name: &str) -> String {
+    match name {
+        "width" => "W = τ-max − τ-min".to_string(),
+        "frac_ties" => "tie fraction".to_string(),
+        "scaled_longest_tie" => "longest tie / n".to_string(),
+        _ => match name.strip_prefix("t_").map(|m| m.split_once('_')) {
+            Some(Some((measure, weight))) => format!("τ-{measure} ({weight})"),
+            Some(None) => format!("τ-{}", &name[2..]),
+            None => name.replace('_', " "),
+        },
+    }
+}
+
+/// how many of `values` fall in each of `bins` equal bins over `range`. the
+/// ones outside it are left out, except for the end of the last bin.
+///
+/// ```
+/// # use eval::plot::histogram;
+/// let counts = histogram(&[-1.0, -0.9, 0.0, 0.5, 1.0, 2.0, f64::NAN], (-1.0, 1.0), 4);
+/// assert_eq!(counts, vec![2, 0, 1, 2]);
+/// assert!(histogram(&[0.0], (-1.0, 1.0), 0).is_empty());
+/// ```
+pub fn histogram(values: &[f64], range: (f64, f64), bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    for &v in values {
+        if let Some(i) = bin_of(v, range, bins) {
+            counts[i] += 1;
+        }
+    }
+    counts
+}
+
+/// how many of the points `(xs[i], ys[i])` fall in each of the `bins` × `bins`
+/// cells over the ranges, indexed `[x][y]`
+///
+/// ```
+/// # use eval::plot::histogram_2d;
+/// let counts = histogram_2d(&[0.0, 0.9, 0.9], &[0.0, 0.9, 1.0], (0.0, 1.0), (0.0, 1.0), 2);
+/// assert_eq!(counts, vec![vec![1, 0], vec![0, 2]]);
+/// ```
+pub fn histogram_2d(
+    xs: &[f64],
+    ys: &[f64],
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    bins: usize,
+) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; bins]; bins];
+    for (&x, &y) in xs.iter().zip(ys) {
+        if let (Some(i), Some(j)) = (bin_of(x, x_range, bins), bin_of(y, y_range, bins)) {
+            counts[i][j] += 1;
+        }
+    }
+    counts
+}
+
+fn bin_of(v: f64, (from, to): (f64, f64), bins: usize) -> Option<usize> {
+    if bins == 0 || !(from..=to).contains(&v) {
+        return None;
+    }
+    let i = ((v - from) / (to - from) * bins as f64) as usize;
+    Some(i.min(bins - 1))
+}
+
+/// the magma colour at `t` from 0 to 1
+fn magma(t: f64) -> RGBColor {
+    let t = t.clamp(0.0, 1.0) * (MAGMA.len() - 1) as f64;
+    let i = (t as usize).min(MAGMA.len() - 2);
+    let f = t - i as f64;
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+    let ((r0, g0, b0), (r1, g1, b1)) = (MAGMA[i], MAGMA[i + 1]);
+    RGBColor(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
+
+/// where the common sans-serif fonts are installed, for when fontconfig can't
+/// tell us
+const SYSTEM_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/liberation-sans/LiberationSans-Regular.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+/// the sans-serif font of the system: the one fontconfig picks, or else the
+/// first of [`SYSTEM_FONTS`] there is
+fn system_font() -> Option<PathBuf> {
+    let matched = Command::new("fc-match")
+        .args(["--format=%{file}", "sans-serif"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| PathBuf::from(String::from_utf8_lossy(&out.stdout).trim()))
+        .filter(|path| path.is_file());
+    matched.or_else(|| {
+        SYSTEM_FONTS
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+    })
+}
+
+/// make the font at `path`, or else the one of the system, the one the figures
+/// are in
+pub fn load_font(path: Option<&Path>) -> Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => system_font()
+            .ok_or_else(|| anyhow!("no sans-serif font found, give one with --font"))?,
+    };
+    let bytes = std::fs::read(&path)
+        .map_err(|e| anyhow!("can't read the font {}: {e}", path.display()))?;
+    // the font has to live as long as the figures, which is the whole run
+    let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+    plotters::style::register_font(FONT, FontStyle::Normal, bytes)
+        .map_err(|_| anyhow!("{} isn't a font", path.display()))?;
+    Ok(())
+}
+
+/// something that can be drawn from a [`Table`]
+pub trait Figure {
+    /// in pixels
+    const SIZE: (u32, u32);
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        table: &Table,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<()>
+    where
+        DB::ErrorType: 'static;
+}
+
+/// draw `figure` to `out`, an svg or png file
+pub fn save<F: Figure>(figure: &F, table: &Table, out: &Path) -> Result<()> {
+    match out.extension().and_then(|e| e.to_str()) {
+        Some("svg") => {
+            let root = SVGBackend::new(out, F::SIZE).into_drawing_area();
+            figure.draw(table, &root)?;
+            root.present()?;
+        }
+        Some("png") => {
+            let root = BitMapBackend::new(out, F::SIZE).into_drawing_area();
+            figure.draw(table, &root)?;
+            root.present()?;
+        }
+        _ => bail!("can only draw to .svg or .png, not {}", out.display()),
+    }
+    Ok(())
+}
+
+impl Figure for Heat {
+    const SIZE: (u32, u32) = (900, 900);
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        table: &Table,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        let xs = table.column(&self.x)?;
+        let ys = table.column(&self.y)?;
+        let x_range = axis_range(&self.x, &xs);
+        let y_range = axis_range(&self.y, &ys);
+        let counts = histogram_2d(&xs, &ys, x_range, y_range, self.bins);
+        let most = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, (FONT, 32))
+            .margin(20)
+            .x_label_area_size(70)
+            .y_label_area_size(80)
+            .build_cartesian_2d(x_range.0..x_range.1, y_range.0..y_range.1)?;
+        chart
+            .configure_mesh()
+            .x_desc(axis_label(&self.x))
+            .y_desc(axis_label(&self.y))
+            .axis_desc_style((FONT, 28))
+            .label_style((FONT, 20))
+            .light_line_style(RGBColor(0xe5, 0xe5, 0xe5))
+            .bold_line_style(RGBColor(0xcc, 0xcc, 0xcc))
+            .draw()?;
+
+        let dx = (x_range.1 - x_range.0) / self.bins as f64;
+        let dy = (y_range.1 - y_range.0) / self.bins as f64;
+        chart.draw_series(counts.iter().enumerate().flat_map(|(i, column)| {
+            column
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c > 0)
+                .map(move |(j, &c)| {
+                    // square root, so the few cases far from the rest still show
+                    let colour = magma((c as f64 / most as f64).sqrt());
+                    let x = x_range.0 + i as f64 * dx;
+                    let y = y_range.0 + j as f64 * dy;
+                    Rectangle::new([(x, y), (x + dx, y + dy)], colour.filled())
+                })
+        }))?;
+
+        if let Some(alpha) = self.diagonal.filter(|a| *a > 0.0) {
+            let from = x_range.0.max(y_range.0);
+            let to = x_range.1.min(y_range.1);
+            chart.draw_series(LineSeries::new(
+                [(from, from), (to, to)],
+                RGBColor(0xff, 0x20, 0x50).mix(alpha).stroke_width(3),
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+impl Figure for Dist {
+    const SIZE: (u32, u32) = (1050, 900);
+
+    fn draw<DB: DrawingBackend>(
+        &self,
+        table: &Table,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        if self.by.len() > DIST_COLOURS.len() {
+            bail!("can only tell {} groups apart", DIST_COLOURS.len());
+        }
+        let values = table.column(&self.of)?;
+        let by = self
+            .by
+            .iter()
+            .map(|c| table.column(c))
+            .collect::<Result<Vec<_>>>()?;
+        // every case goes to the first group it's over the threshold in
+        let mut groups = vec![Vec::new(); self.by.len()];
+        for (i, &v) in values.iter().enumerate() {
+            if let Some(g) = by.iter().position(|column| column[i] >= self.above) {
+                groups[g].push(v);
+            }
+        }
+
+        let included = groups.iter().flatten().copied().collect::<Vec<_>>();
+        let (from, to) = axis_range("", &included);
+        // a little on either side, so the bars at the ends show
+        let range = (from - 0.02, to + 0.02);
+        let counts = groups
+            .iter()
+            .map(|g| histogram(g, range, self.bins))
+            .collect::<Vec<_>>();
+        let most = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, (FONT, 32))
+            .margin(20)
+            .x_label_area_size(70)
+            .y_label_area_size(90)
+            .build_cartesian_2d(range.0..range.1, 0.0..most as f64 * 1.05)?;
+        chart
+            .configure_mesh()
+            .x_desc(axis_label(&self.of))
+            .y_desc("count")
+            .y_label_formatter(&|c| format!("{c:.0}"))
+            .axis_desc_style((FONT, 28))
+            .label_style((FONT, 20))
+            .light_line_style(RGBColor(0xe5, 0xe5, 0xe5))
+            .bold_line_style(RGBColor(0xcc, 0xcc, 0xcc))
+            .draw()?;
+
+        let width = (range.1 - range.0) / self.bins as f64;
+        for ((name, counts), colour) in self.by.iter().zip(&counts).zip(DIST_COLOURS) {
+            chart
+                .draw_series(counts.iter().enumerate().map(|(i, &c)| {
+                    let x = range.0 + i as f64 * width;
+                    Rectangle::new(
+                        [(x, 0.0), (x + width, c as f64)],
+                        colour.mix(0.85).filled(),
+                    )
+                }))?
+                .label(format!("{} ≥ {}", axis_label(name), self.above))
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 8), (x + 16, y + 8)], colour.filled())
+                });
+        }
+        for &line in &self.lines {
+            chart.draw_series(DashedLineSeries::new(
+                [(line, 0.0), (line, most as f64 * 1.05)],
+                12,
+                8,
+                RGBColor(0x30, 0x15, 0x45).stroke_width(3),
+            ))?;
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .label_font((FONT, 24))
+            .background_style(WHITE.mix(0.8))
+            .border_style(RGBColor(0xcc, 0xcc, 0xcc))
+            .draw()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! draw the figures of `eval` outputs to svg or png (see [`eval::plot`])
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clap_derive::Parser;
+use clap_derive::Subcommand;
+use eval::plot::Dist;
+use eval::plot::Heat;
+use eval::plot::Table;
+use eval::plot::axis_label;
+use eval::plot::load_font;
+use eval::plot::save;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// outputs of eval
+    pub data: String,
+    /// a ttf or otf file for the text, instead of the sans-serif font of the
+    /// system
+    #[arg(long)]
+    pub font: Option<PathBuf>,
+    /// what the data is, for the titles
+    #[arg(long, default_value = "synthetic data")]
+    pub name: String,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// a 2d histogram of one column against another
+    Heat {
+        output: PathBuf,
+        #[arg(long, default_value = "t_b")]
+        x: String,
+        #[arg(long, default_value = "t_min")]
+        y: String,
+        #[arg(long, default_value_t = 100, value_parser = parse_bins)]
+        bins: usize,
+        /// draw the diagonal, this opaque
+        #[arg(long)]
+        diagonal: Option<f64>,
+    },
+    /// histograms of a column for the cases where others are over a threshold
+    Dist {
+        output: PathBuf,
+        #[arg(long, default_value = "t_min")]
+        of: String,
+        #[arg(long, value_delimiter = ',', default_value = "t_a,t_b")]
+        by: Vec<String>,
+        #[arg(long, default_value_t = 0.9)]
+        above: f64,
+        #[arg(long, default_value_t = 110, value_parser = parse_bins)]
+        bins: usize,
+        /// where to draw dashed lines
+        #[arg(long, value_delimiter = ',', default_value = "0.8,0.9")]
+        lines: Vec<f64>,
+    },
+    /// the figures of `plots/*.R`: t_max and t_min against t_a and t_b, the
+    /// width against the tie fraction and the distribution of t_min
+    All {
+        dir: PathBuf,
+        /// svg or png
+        #[arg(long, default_value = "svg")]
+        format: String,
+        /// draw the diagonals, this opaque
+        #[arg(long)]
+        diagonal: Option<f64>,
+    },
+}
+
+fn parse_bins(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("there has to be at least one bin".to_string()),
+        bins => bins.map_err(|e| e.to_string()),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    load_font(args.font.as_deref())?;
+    let table = Table::read(&args.data)?;
+    let title =
+        |x: &str, y: &str| format!("{}: {} vs {}", args.name, axis_label(y), axis_label(x));
+
+    match &args.command {
+        Command::Heat {
+            output,
+            x,
+            y,
+            bins,
+            diagonal,
+        } => {
+            let heat = Heat {
+                x: x.clone(),
+                y: y.clone(),
+                bins: *bins,
+                title: title(x, y),
+                diagonal: *diagonal,
+            };
+            save(&heat, &table, output)?;
+        }
+        Command::Dist {
+            output,
+            of,
+            by,
+            above,
+            bins,
+            lines,
+        } => {
+            let dist = Dist {
+                of: of.clone(),
+                by: by.clone(),
+                above: *above,
+                bins: *bins,
+                title: format!(
+                    "{}: {} for the cases over {above}",
+                    args.name,
+                    axis_label(of)
+                ),
+                lines: lines.clone(),
+            };
+            save(&dist, &table, output)?;
+        }
+        Command::All {
+            dir,
+            format,
+            diagonal,
+        } => {
+            std::fs::create_dir_all(dir)?;
+            let heats = [
+                ("t_a", "t_max", "tmax_a"),
+                ("t_b", "t_max", "tmax_b"),
+                ("t_a", "t_min", "tmin_a"),
+                ("t_b", "t_min", "tmin_b"),
+                ("frac_ties", "width", "width_ties"),
+            ];
+            for (x, y, file) in heats {
+                let heat = Heat {
+                    x: x.to_string(),
+                    y: y.to_string(),
+                    bins: 100,
+                    title: title(x, y),
+                    diagonal: diagonal.filter(|_| x.starts_with("t_")),
+                };
+                let output = dir.join(format!("{file}.{format}"));
+                save(&heat, &table, &output)?;
+                println!("{}", output.display());
+            }
+            let dist = Dist {
+                of: "t_min".to_string(),
+                by: vec!["t_a".to_string(), "t_b".to_string()],
+                above: 0.9,
+                bins: 110,
+                title: format!("{}: τ-min for τ-a ≥ 0.9 and τ-b ≥ 0.9", args.name),
+                lines: vec![0.8, 0.9],
+            };
+            let output = dir.join(format!("dist.{format}"));
+            save(&dist, &table, &output)?;
+            println!("{}", output.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! how often does tau_b look like a strong correlation when it isn't robust?
+//!
+//! a case is flagged at a threshold when its `t_b` is at least the threshold,
+//! but its `t_min` is below it: some arbitration of its ties correlates less.
+//! both are compared up to [`PRECISION`], so a `t_min` that's the threshold
+//! but for rounding isn't below it.
+//! the flagged cases are counted over all cases, and by length, tie fraction
+//! and longest tie.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use csv::StringRecord;
+use lib::PRECISION;
+
+/// what the report needs from a row of `eval` output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    /// the input, empty if the output doesn't have it
+    pub a: String,
+    pub b: String,
+    pub t_b: f64,
+    pub t_min: f64,
+    pub length: usize,
+    pub frac_ties: f64,
+    pub longest_tie: usize,
+}
+
+/// one line of the summary: at `threshold`, of the cases in `group` (such as
+/// `length=7`), `above` have a `t_b` at least the threshold, and `flagged` of
+/// those have a `t_min` below it.
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize)]
+pub struct SummaryRow {
+    pub threshold: f64,
+    pub by: String,
+    pub group: String,
+    pub cases: usize,
+    pub above: usize,
+    pub flagged: usize,
+    /// flagged out of above
+    pub rate: f64,
+}
+
+/// a flagged case, with how far its `t_min` is below its `t_b`
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize)]
+pub struct FlaggedRow {
+    pub threshold: f64,
+    pub a: String,
+    pub b: String,
+    pub t_b: f64,
+    pub t_min: f64,
+    pub gap: f64,
+    pub length: usize,
+    pub frac_ties: f64,
+    pub longest_tie: usize,
+}
+
+/// the tie fractions are put in bins this wide
+pub const FRAC_BIN: f64 = 0.1;
+
+/// the name of a breakdown, and the group of a case in it, with where that
+/// group goes in the summary
+type Grouping = (&'static str, fn(&Case) -> (usize, String));
+
+impl Case {
+    /// the case in a row of `eval` output with `header`. the taus are the ones
+    /// of `weight`, when there are several.
+    ///
+    /// ```
+    /// # use csv::StringRecord;
+    /// # use eval::report::Case;
+    /// let header = StringRecord::from(vec!["t_b", "t_min", "length", "frac_ties", "longest_tie"]);
+    /// let record = StringRecord::from(vec!["0.9", "0.8999999999", "4", "0.5", "2"]);
+    /// let case = Case::from_record(&record, &header, None).unwrap();
+    /// assert_eq!((case.a.as_str(), case.length), ("", 4));
+    /// ```
+    pub fn from_record(
+        record: &StringRecord,
+        header: &StringRecord,
+        weight: Option<&str>,
+    ) -> Result<Case> {
+        let field = |name: &str| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .map(|i| &record[i])
+                .ok_or_else(|| anyhow!("there's no {name:?} column"))
+        };
+        let tau = |measure: &str| {
+            let name =
+                weight.map_or(format!("t_{measure}"), |w| format!("t_{measure}_{w}"));
+            field(&name)?
+                .parse::<f64>()
+                .map_err(|e| anyhow!("bad {name}: {e}"))
+        };
+        Ok(Case {
+            a: field("a").unwrap_or_default().to_string(),
+            b: field("b").unwrap_or_default().to_string(),
+            t_b: tau("b")?,
+            t_min: tau("min")?,
+            length: field("length")?.parse()?,
+            frac_ties: field("frac_ties")?.parse()?,
+            longest_tie: field("longest_tie")?.parse()?,
+        })
+    }
+
+    fn is_above(&self, threshold: f64) -> bool {
+        self.t_b >= threshold - PRECISION
+    }
+
+    fn is_flagged(&self, threshold: f64) -> bool {
+        self.is_above(threshold) && self.t_min < threshold - PRECISION
+    }
+}
+
+/// the summary and the flagged cases of every threshold
+///
+/// ```
+/// # use eval::report::Case;
+/// # use eval::report::report;
+/// let case = |t_b, t_min, length| Case {
+///     a: String::new(),
+///     b: String::new(),
+///     t_b,
+///     t_min,
+///     length,
+///     frac_ties: 0.25,
+///     longest_tie: 2,
+/// };
+/// let cases = [case(0.95, 0.7, 5), case(0.92, 0.91, 5), case(0.5, 0.1, 6)];
+/// let (summary, flagged) = report(&cases, &[0.8, 0.9]);
+/// assert_eq!(flagged.len(), 2);
+/// let all_09 = summary.iter().find(|s| s.threshold == 0.9 && s.by == "all").unwrap();
+/// assert_eq!((all_09.cases, all_09.above, all_09.flagged), (3, 2, 1));
+///
+/// // t_b and t_min are the threshold but for rounding
+/// let cases = [case(0.9 - 1e-12, 0.7, 5), case(0.95, 0.9 - 1e-12, 5)];
+/// let (_, flagged) = report(&cases, &[0.9]);
+/// assert_eq!(flagged.len(), 1);
+/// assert_eq!(flagged[0].t_min, 0.7);
+/// ```
+pub fn report(cases: &[Case], thresholds: &[f64]) -> (Vec<SummaryRow>, Vec<FlaggedRow>) {
+    let mut summary = Vec::new();
+    let mut flagged = Vec::new();
+    for &threshold in thresholds {
+        let groupings: [Grouping; 4] = [
+            ("all", |_| (0, "all".to_string())),
+            ("length", |c| (c.length, c.length.to_string())),
+            ("frac_ties", |c| {
+                // a little over, so that 0.3 isn't put in 0.2-0.3
+                let bin = (c.frac_ties / FRAC_BIN + 1e-9).floor() as usize;
+                let from = bin as f64 * FRAC_BIN;
+                (bin, format!("{from:.1}-{:.1}", from + FRAC_BIN))
+            }),
+            ("longest_tie", |c| {
+                (c.longest_tie, c.longest_tie.to_string())
+            }),
+        ];
+        for (by, group_of) in groupings {
+            // (cases, above, flagged)
+            let mut groups = BTreeMap::<(usize, String), (usize, usize, usize)>::new();
+            for c in cases {
+                let counts = groups.entry(group_of(c)).or_default();
+                counts.0 += 1;
+                counts.1 += usize::from(c.is_above(threshold));
+                counts.2 += usize::from(c.is_flagged(threshold));
+            }
+            summary.extend(groups.into_iter().map(|((_, group), (n, above, f))| {
+                SummaryRow {
+                    threshold,
+                    by: by.to_string(),
+                    group,
+                    cases: n,
+                    above,
+                    flagged: f,
+                    rate: if above == 0 {
+                        0.0
+                    } else {
+                        f as f64 / above as f64
+                    },
+                }
+            }));
+        }
+
+        flagged.extend(cases.iter().filter(|c| c.is_flagged(threshold)).map(|c| {
+            FlaggedRow {
+                threshold,
+                a: c.a.clone(),
+                b: c.b.clone(),
+                t_b: c.t_b,
+                t_min: c.t_min,
+                gap: c.t_b - c.t_min,
+                length: c.length,
+                frac_ties: c.frac_ties,
+                longest_tie: c.longest_tie,
+            }
+        }));
+    }
+    (summary, flagged)
+}
@@ -0,0 +1,72 @@
+//! report the cases of `eval` outputs where tau_b isn't robust (see
+//! [`eval::report`])
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clap_derive::Parser;
+use csv::Writer;
+use eval::is_finished;
+use eval::report::Case;
+use eval::report::report;
+use glob::glob;
+use lib::open_csv;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// outputs of eval
+    pub data: String,
+    /// where the summary goes
+    pub summary: PathBuf,
+    /// where the flagged cases go
+    pub flagged: PathBuf,
+    #[arg(long, value_delimiter = ',', default_value = "0.8,0.9")]
+    pub thresholds: Vec<f64>,
+    /// the weight whose taus to look at, if eval had several
+    #[arg(long)]
+    pub weight: Option<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+
+    let mut cases = Vec::new();
+    for path in glob(&args.data)? {
+        let mut reader = open_csv(&path?, &[])?;
+        let header = reader.headers()?.clone();
+        for record in reader.records() {
+            let record = record?;
+            // the ones that went over the limits have no taus
+            if !is_finished(&header, &record) {
+                continue;
+            }
+            cases.push(Case::from_record(&record, &header, args.weight.as_deref())?);
+        }
+    }
+
+    let (summary, flagged) = report(&cases, &args.thresholds);
+
+    let mut writer = Writer::from_path(&args.summary)?;
+    for row in &summary {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    let mut writer = Writer::from_path(&args.flagged)?;
+    for row in &flagged {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+
+    println!("{} cases", cases.len());
+    for row in summary.iter().filter(|r| r.by == "all") {
+        println!(
+            "tau_b >= {}: {} cases, {} of them ({:.1}%) with tau_min below",
+            row.threshold,
+            row.above,
+            row.flagged,
+            100.0 * row.rate
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,309 @@
+//! results of solvers, kept on disk between runs
+//!
+//! an entry is what a solver said about an input: its bounds and optimal
+//! arbitrations, and what the run cost. it's keyed by the canonical form of the
+//! input (see [`crate::canon`]), the weight and the solver, so the same input
+//! under other names is a hit too. the arbitrations are kept with the canonical
+//! names, and given back with the names of the input that's asked for.
+//!
+//! every entry is a small csv file, named after the hash of its key. an external
+//! solver is named by a hash of its executable (see [`solver_id`]), so a rebuilt
+//! solver starts from an empty cache. the reference, which runs in process, is
+//! named by a version instead (see `solutions::ref_solver::VERSION`).
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use csv::Reader;
+use csv::Writer;
+
+use crate::AlgoOut;
+use crate::RankingsCsvRow;
+use crate::Usage;
+use crate::canon::CanonicalPair;
+use crate::canon::renaming;
+use crate::def::*;
+use crate::display_cases;
+
+/// a directory of cached results
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// one cached result, as it's on disk
+#[derive(Debug, Clone, serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct CacheEntry {
+    /// the canonical form of the input
+    pub input: String,
+    pub weight: String,
+    pub solver: String,
+    /// no bounds: the solver skipped the input
+    pub tmin: Option<f64>,
+    pub tmax: Option<f64>,
+    /// optimal arbitrations with the canonical names, as in [`display_cases`]
+    pub pmin: String,
+    pub pmax: String,
+    /// how many optimal arbitrations there are, if the solver counted them.
+    /// entries from before they were kept don't have them.
+    #[serde(default)]
+    pub nmin: Option<u128>,
+    #[serde(default)]
+    pub nmax: Option<u128>,
+    /// wall-clock and cpu seconds, and peak rss in KiB, of the run
+    pub time: f64,
+    pub cpu: f64,
+    pub rss: u64,
+    /// seconds since the epoch
+    pub created: u64,
+}
+
+/// a result for an input, with its names
+#[derive(Debug)]
+pub struct Cached {
+    /// `None` if the solver skipped it
+    pub out: Option<AlgoOut>,
+    pub time: Duration,
+    pub usage: Usage,
+}
+
+impl Cache {
+    pub fn open(dir: &Path) -> Result<Cache> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("couldn't create the cache {}: {e}", dir.display()))?;
+        Ok(Cache {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// the result of `solver` with `weight` on `row`, if there is one
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use lib::AlgoOut;
+    /// # use lib::RankingsCsvRow;
+    /// # use lib::Usage;
+    /// # use lib::cache::Cache;
+    /// # use lib::cache::Cached;
+    /// let dir = std::env::temp_dir().join(format!("tau-cache-doc-{}", std::process::id()));
+    /// let cache = Cache::open(&dir).unwrap();
+    /// let row = |a: &str, b: &str| RankingsCsvRow {
+    ///     a: a.to_string(),
+    ///     b: b.to_string(),
+    /// };
+    /// let out = AlgoOut {
+    ///     tmin: Some(-1.0),
+    ///     tmax: Some(1.0),
+    ///     minp: vec![("x y".to_string(), "y x".to_string())],
+    ///     maxp: vec![("y x".to_string(), "y x".to_string())],
+    ///     nmax: Some(1),
+    ///     ..Default::default()
+    /// };
+    /// let result = Cached {
+    ///     out: Some(out),
+    ///     time: Duration::from_secs(1),
+    ///     usage: Usage::default(),
+    /// };
+    /// cache.put(&row("(x y)", "y x"), "unweighted", "s", &result).unwrap();
+    ///
+    /// // the same input with other names
+    /// let hit = cache.get(&row("(2 1)", "1 2"), "unweighted", "s").unwrap();
+    /// let out = hit.unwrap().out.unwrap();
+    /// assert_eq!(out.maxp, vec![("1 2".to_string(), "1 2".to_string())]);
+    /// assert_eq!((out.nmin, out.nmax), (None, Some(1)));
+    /// assert!(cache.get(&row("(2 1)", "1 2"), "ap_weight", "s").unwrap().is_none());
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn get(
+        &self,
+        row: &RankingsCsvRow,
+        weight: &str,
+        solver: &str,
+    ) -> Result<Option<Cached>> {
+        let (input, names) = canonical_names(row)?;
+        let path = self.path(&input, weight, solver);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let entry = read_entry(&path)?;
+        // a hash collision is just a miss
+        if entry.input != input || entry.weight != weight || entry.solver != solver {
+            return Ok(None);
+        }
+
+        let back = names
+            .into_iter()
+            .map(|(token, name)| (name, token))
+            .collect::<BTreeMap<String, String>>();
+        let out = match (entry.tmin, entry.tmax) {
+            (None, None) => None,
+            (tmin, tmax) => Some(AlgoOut {
+                tmin,
+                tmax,
+                minp: rename_cases(&entry.pmin, &back)?,
+                maxp: rename_cases(&entry.pmax, &back)?,
+                nmin: entry.nmin,
+                nmax: entry.nmax,
+                ..Default::default()
+            }),
+        };
+        Ok(Some(Cached {
+            out,
+            time: Duration::from_secs_f64(entry.time),
+            usage: Usage {
+                cpu: Duration::from_secs_f64(entry.cpu),
+                max_rss: entry.rss,
+            },
+        }))
+    }
+
+    /// keep the result of `solver` with `weight` on `row`
+    pub fn put(
+        &self,
+        row: &RankingsCsvRow,
+        weight: &str,
+        solver: &str,
+        result: &Cached,
+    ) -> Result<()> {
+        let (input, names) = canonical_names(row)?;
+        let cases = |ps: &[(String, String)]| {
+            let renamed = ps
+                .iter()
+                .map(|(a, b)| Ok((rename(a, &names)?, rename(b, &names)?)))
+                .collect::<Result<Vec<(String, String)>>>()?;
+            Ok::<String, anyhow::Error>(display_cases(&renamed))
+        };
+        let entry = CacheEntry {
+            input,
+            weight: weight.to_string(),
+            solver: solver.to_string(),
+            tmin: result.out.as_ref().and_then(|o| o.tmin),
+            tmax: result.out.as_ref().and_then(|o| o.tmax),
+            pmin: result
+                .out
+                .as_ref()
+                .map_or(Ok(String::new()), |o| cases(&o.minp))?,
+            pmax: result
+                .out
+                .as_ref()
+                .map_or(Ok(String::new()), |o| cases(&o.maxp))?,
+            nmin: result.out.as_ref().and_then(|o| o.nmin),
+            nmax: result.out.as_ref().and_then(|o| o.nmax),
+            time: result.time.as_secs_f64(),
+            cpu: result.usage.cpu.as_secs_f64(),
+            rss: result.usage.max_rss,
+            created: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+
+        let path = self.path(&entry.input, weight, solver);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // write next to it and move it in, so no one reads half an entry
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        let mut writer = Writer::from_path(&tmp)?;
+        writer.serialize(&entry)?;
+        writer.flush()?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// every entry, with the file it's in
+    pub fn entries(&self) -> Result<Vec<(PathBuf, CacheEntry)>> {
+        let mut entries = Vec::new();
+        for sub in std::fs::read_dir(&self.dir)? {
+            let sub = sub?.path();
+            if !sub.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(&sub)? {
+                let file = file?.path();
+                if file.extension().is_some_and(|e| e == "csv") {
+                    entries.push((file.clone(), read_entry(&file)?));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn path(&self, input: &str, weight: &str, solver: &str) -> PathBuf {
+        let hash = fnv1a(format!("{input}\n{weight}\n{solver}").as_bytes());
+        let hex = format!("{hash:016x}");
+        self.dir.join(&hex[..2]).join(format!("{}.csv", &hex[2..]))
+    }
+}
+
+/// names a solver by a hash of its executable
+pub fn solver_id(exe: &Path) -> Result<String> {
+    let bytes = std::fs::read(exe)
+        .map_err(|e| anyhow!("couldn't read the solver {}: {e}", exe.display()))?;
+    let name = exe
+        .file_name()
+        .map_or(String::new(), |n| n.to_string_lossy().to_string());
+    Ok(format!("{name}-{:016x}", fnv1a(&bytes)))
+}
+
+fn read_entry(path: &Path) -> Result<CacheEntry> {
+    let mut reader = Reader::from_reader(File::open(path)?);
+    reader
+        .deserialize()
+        .next()
+        .ok_or_else(|| anyhow!("empty cache entry {}", path.display()))?
+        .map_err(|e| anyhow!("broken cache entry {}: {e}", path.display()))
+}
+
+/// the canonical form of a row, and the canonical name of each of its tokens
+fn canonical_names(row: &RankingsCsvRow) -> Result<(String, BTreeMap<String, String>)> {
+    let mut inp_map = BTreeMap::new();
+    let a = partial_from_string(&row.a, &mut inp_map)?;
+    let b = partial_from_string(&row.b, &mut inp_map)?;
+    let names = renaming(&a, &b);
+    let tokens = inp_map
+        .into_iter()
+        .map(|(token, e)| (token, names[&e].to_string()))
+        .collect();
+    Ok((CanonicalPair::new(&a, &b).to_string(), tokens))
+}
+
+fn rename(s: &str, names: &BTreeMap<String, String>) -> Result<String> {
+    s.split_whitespace()
+        .map(|t| {
+            names
+                .get(t)
+                .cloned()
+                .ok_or_else(|| anyhow!("{t:?} isn't an item of the input"))
+        })
+        .collect::<Result<Vec<String>>>()
+        .map(|ts| ts.join(" "))
+}
+
+/// the other way around from [`display_cases`]
+fn rename_cases(
+    s: &str,
+    names: &BTreeMap<String, String>,
+) -> Result<Vec<(String, String)>> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    s.split('|')
+        .map(|case| {
+            let (a, b) = case
+                .split_once('/')
+                .ok_or_else(|| anyhow!("broken arbitration {case:?} in the cache"))?;
+            Ok((rename(a, names)?, rename(b, names)?))
+        })
+        .collect()
+}
+
+/// 64 bit fnv-1a, which (unlike the std hashers) is the same everywhere
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,113 @@
+//! look into, prune and export a result cache (see [`lib::cache`])
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use anyhow::ensure;
+use clap::Parser;
+use clap_derive::Parser;
+use clap_derive::Subcommand;
+use csv::Writer;
+use lib::RankingsCsvRow;
+use lib::cache::Cache;
+use lib::canon::CanonicalPair;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    pub cache: PathBuf,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// how many entries there are per solver and weight, or the entries of
+    /// one input
+    Inspect {
+        a: Option<String>,
+        b: Option<String>,
+    },
+    /// remove the entries that match all of the filters
+    Prune {
+        #[arg(long)]
+        solver: Option<String>,
+        #[arg(long)]
+        weight: Option<String>,
+        /// made more than this many days ago
+        #[arg(long)]
+        older_than: Option<f64>,
+        /// remove everything, if there are no filters
+        #[arg(long)]
+        all: bool,
+    },
+    /// write every entry to a csv file
+    Export { output: PathBuf },
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let cache = Cache::open(&args.cache)?;
+    let entries = cache.entries()?;
+
+    match args.command {
+        Command::Inspect {
+            a: Some(a),
+            b: Some(b),
+        } => {
+            let input = CanonicalPair::from_row(&RankingsCsvRow { a, b })?.to_string();
+            println!("canonical form: {input}");
+            for (_, e) in entries.iter().filter(|(_, e)| e.input == input) {
+                println!(
+                    "{} {}: tmin {:?}, tmax {:?} ({}s)",
+                    e.solver, e.weight, e.tmin, e.tmax, e.time
+                );
+            }
+        }
+        Command::Inspect { .. } => {
+            let mut counts = BTreeMap::new();
+            for (_, e) in &entries {
+                *counts.entry((&e.solver, &e.weight)).or_insert(0) += 1;
+            }
+            for ((solver, weight), n) in counts {
+                println!("{solver} {weight}: {n}");
+            }
+            println!("{} entries", entries.len());
+        }
+        Command::Prune {
+            solver,
+            weight,
+            older_than,
+            all,
+        } => {
+            ensure!(
+                all || solver.is_some() || weight.is_some() || older_than.is_some(),
+                "give a filter, or --all to remove everything"
+            );
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let mut removed = 0;
+            for (path, e) in &entries {
+                let age_days = now.saturating_sub(e.created) as f64 / 86400.0;
+                if solver.as_ref().is_none_or(|s| *s == e.solver)
+                    && weight.as_ref().is_none_or(|w| *w == e.weight)
+                    && older_than.is_none_or(|d| age_days > d)
+                {
+                    std::fs::remove_file(path)?;
+                    removed += 1;
+                }
+            }
+            println!("removed {removed} of {} entries", entries.len());
+        }
+        Command::Export { output } => {
+            let mut writer = Writer::from_path(&output)?;
+            for (_, e) in &entries {
+                writer.serialize(e)?;
+            }
+            writer.flush()?;
+            println!("exported {} entries to {}", entries.len(), output.display());
+        }
+    }
+    Ok(())
+}
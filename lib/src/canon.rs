@@ -0,0 +1,140 @@
+//! pairs of rankings up to renaming their items
+//!
+//! what the solvers compute only depends on where the items of a pair are, not
+//! on what they're called: an item is just its tie group in a and its tie group
+//! in b. so two pairs are the same input if they have the same number of items
+//! at every (tie group in a, tie group in b). the canonical form names the
+//! items in order of those two ranks, which makes it the same for all of them.
+//!
+//! for a pair out of [`crate::rwt::distinct_pairs`] that's the pair itself.
+//!
+//! ```
+//! # use std::collections::BTreeSet;
+//! # use lib::canon::CanonicalPair;
+//! # use lib::rwt::all_pairs;
+//! # use lib::rwt::distinct_pairs;
+//! for (a, b) in distinct_pairs(4) {
+//!     assert_eq!(CanonicalPair::new(&a, &b), CanonicalPair { a, b });
+//! }
+//! let forms = all_pairs(4).map(|(a, b)| CanonicalPair::new(&a, &b));
+//! assert_eq!(forms.collect::<BTreeSet<_>>().len(), distinct_pairs(4).count());
+//! ```
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use anyhow::Result;
+use itertools::Itertools;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+
+use crate::RankingsCsvRow;
+use crate::def::*;
+
+/// the canonical form of a pair, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalPair {
+    pub a: PartialOrder,
+    pub b: PartialOrder,
+}
+
+impl CanonicalPair {
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use lib::canon::CanonicalPair;
+    /// # use lib::def::partial_from_string;
+    /// let canon = |a: &str, b: &str| {
+    ///     let mut map = BTreeMap::new();
+    ///     let a = partial_from_string(a, &mut map).unwrap();
+    ///     let b = partial_from_string(b, &mut map).unwrap();
+    ///     CanonicalPair::new(&a, &b)
+    /// };
+    /// let c = canon("(x y) z", "z y x");
+    /// assert_eq!(c, canon("(p q) r", "r p q"));
+    /// assert_eq!(c.to_string(), "(a b) c; c a b");
+    /// assert_ne!(c, canon("x (y z)", "z y x"));
+    /// ```
+    pub fn new(a: &PartialOrder, b: &PartialOrder) -> CanonicalPair {
+        let names = renaming(a, b);
+        let rename = |r: &PartialOrder| {
+            r.iter()
+                .map(|tg| tg.iter().map(|e| names[e]).sorted().collect())
+                .collect()
+        };
+        CanonicalPair {
+            a: rename(a),
+            b: rename(b),
+        }
+    }
+
+    /// the canonical form of a row of input
+    pub fn from_row(row: &RankingsCsvRow) -> Result<CanonicalPair> {
+        let mut map = BTreeMap::new();
+        let a = partial_from_string(&row.a, &mut map)?;
+        let b = partial_from_string(&row.b, &mut map)?;
+        Ok(CanonicalPair::new(&a, &b))
+    }
+}
+
+impl Display for CanonicalPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}; {}",
+            partial_to_string(&self.a),
+            partial_to_string(&self.b)
+        )
+    }
+}
+
+/// the names [`CanonicalPair::new`] gives the items of `a` and `b`
+pub fn renaming(a: &PartialOrder, b: &PartialOrder) -> BTreeMap<Element, Element> {
+    let ranks = |r: &PartialOrder| {
+        r.iter()
+            .enumerate()
+            .flat_map(|(rank, tg)| tg.iter().map(move |e| (*e, rank)))
+            .collect::<BTreeMap<Element, usize>>()
+    };
+    let (ranks_a, ranks_b) = (ranks(a), ranks(b));
+
+    // items only in one of them go after the others
+    let key = |e: &Element| {
+        (
+            ranks_a.get(e).copied().unwrap_or(usize::MAX),
+            ranks_b.get(e).copied().unwrap_or(usize::MAX),
+        )
+    };
+    ranks_a
+        .keys()
+        .chain(ranks_b.keys())
+        .unique()
+        .sorted_by_key(|e| key(e))
+        .enumerate()
+        .map(|(i, e)| (*e, element_from_index(i)))
+        .collect()
+}
+
+/// the rows that are a different input than every row before them, in order
+///
+/// ```
+/// # use lib::RankingsCsvRow;
+/// # use lib::canon::dedup_rows;
+/// let row = |a: &str, b: &str| RankingsCsvRow {
+///     a: a.to_string(),
+///     b: b.to_string(),
+/// };
+/// let rows = vec![row("1 2", "2 1"), row("x y", "x y"), row("b a", "a b")];
+/// assert_eq!(dedup_rows(rows).unwrap().len(), 2);
+/// ```
+pub fn dedup_rows(rows: Vec<RankingsCsvRow>) -> Result<Vec<RankingsCsvRow>> {
+    let keys = rows
+        .par_iter()
+        .map(CanonicalPair::from_row)
+        .collect::<Result<Vec<CanonicalPair>>>()?;
+    let mut seen = HashSet::new();
+    Ok(rows
+        .into_iter()
+        .zip(keys)
+        .filter_map(|(row, key)| seen.insert(key).then_some(row))
+        .collect())
+}
@@ -39,10 +39,9 @@ impl Ranking for StrictOrder {
     fn ensure_defined(&self) -> Result<Vec<Vec<Element>>> {
         self.iter()
             .map(|x| {
-                x.map(|xx| vec![xx]).ok_or(anyhow!(
-                    "ranking is not fully defined: {}",
-                    total_to_string(self)
-                ))
+                x.map(|xx| vec![xx]).ok_or_else(|| {
+                    anyhow!("ranking is not fully defined: {}", total_to_string(self))
+                })
             })
             .collect::<Result<Vec<Vec<Element>>>>()
     }
@@ -261,8 +260,7 @@ pub fn partial_from_string(
         let elem = if let Some(e) = inp_map.get(&core.to_string()) {
             *e
         } else {
-            let c = char::from_u32(97 + (inp_map.len() as u32))
-                .expect("invalid conversion from string index to char");
+            let c = element_from_index(inp_map.len());
             inp_map.insert(core.to_string(), c);
             c
         };
@@ -299,6 +297,17 @@ pub fn partial_from_string(
     Ok(out)
 }
 
+/// the name of the `i`-th distinct element of an input: `a`, `b`, ... and then
+/// onwards through unicode, skipping the surrogate block (which aren't valid
+/// `char`s) so that rankings with more than ~55k items can still be represented.
+pub fn element_from_index(i: usize) -> Element {
+    let mut c = 97 + i as u32;
+    if c >= 0xD800 {
+        c += 0x800;
+    }
+    char::from_u32(c).expect("invalid conversion from string index to char")
+}
+
 pub fn partial_to_string(r: &PartialOrder) -> String {
     r.iter()
         .map(|tg| {
@@ -362,6 +371,19 @@ pub fn total_to_repl_string(o: &StrictOrder, rmap: &BTreeMap<Element, String>) -
         .join(" ")
 }
 
+/// pairs of elements as `x y,x z`, by their names in the input
+pub fn pairs_to_repl_string(
+    pairs: &[(Element, Element)],
+    rmap: &BTreeMap<Element, String>,
+) -> String {
+    let name = |e: &Element| rmap.get(e).map_or("<nf>".to_string(), |s| s.to_string());
+    pairs
+        .iter()
+        .map(|(x, y)| format!("{} {}", name(x), name(y)))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
 fn join_chars(c: &[char], sep: &str) -> String {
     c.iter()
         .map(|x| x.to_string())
@@ -389,6 +411,22 @@ pub struct Bound {
     pub t: f64,
     pub a: Vec<StrictOrder>,
     pub b: Vec<StrictOrder>,
+    /// how many optimal arbitrations there are, if known. there can be more of
+    /// them than are listed in `a`,`b`.
+    pub count: Option<u128>,
+    /// a certificate that the bound is optimal, if one was asked for: the
+    /// pairs that go against the bound in the first arbitration (in its order
+    /// in `a`), all of them fixed by the input (see
+    /// `solver::bounds::certificate`)
+    pub certificate: Option<Vec<(Element, Element)>>,
+}
+
+impl Bound {
+    /// keep (at most) the first `limit` solutions
+    pub fn truncate(&mut self, limit: usize) {
+        self.a.truncate(limit);
+        self.b.truncate(limit);
+    }
 }
 
 pub struct TauBounds {
@@ -426,6 +464,12 @@ impl TauBounds {
         let mut out = String::from("\n");
         if let Some(lb) = &self.lb {
             out.push_str(&format!("tmin:{:?}\n", lb.t));
+            if let Some(n) = lb.count {
+                out.push_str(&format!("nmin:{n}\n"));
+            }
+            if let Some(c) = &lb.certificate {
+                out.push_str(&format!("cmin:{}\n", pairs_to_repl_string(c, &rmap)));
+            }
             for (mina, minb) in lb.a.iter().zip(lb.b.iter()) {
                 out.push_str(&format!(
                     "minp:{}/{}\n",
@@ -436,6 +480,12 @@ impl TauBounds {
         }
         if let Some(ub) = &self.ub {
             out.push_str(&format!("tmax:{:?}\n", ub.t));
+            if let Some(n) = ub.count {
+                out.push_str(&format!("nmax:{n}\n"));
+            }
+            if let Some(c) = &ub.certificate {
+                out.push_str(&format!("cmax:{}\n", pairs_to_repl_string(c, &rmap)));
+            }
             for (maxa, maxb) in ub.a.iter().zip(ub.b.iter()) {
                 out.push_str(&format!(
                     "maxp:{}/{}\n",
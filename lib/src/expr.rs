@@ -0,0 +1,255 @@
+//! weight functions given as expressions, like `1/(xa+1) * 1/(ya+1)`
+//!
+//! an expression can use the positions of the two elements of a pair: `xa`,
+//! `xb` (of the first in a and b) and `ya`, `yb` (of the second), numbers,
+//! `+ - * / ^`, parentheses, and the functions `ln`, `exp`, `sqrt`, `abs`,
+//! `min` and `max`.
+//!
+//! a [`WeightFn`] holds a plain function pointer, so it can't carry an
+//! expression around: [`expression_weight`] stores it once per process instead.
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+
+use crate::weights::WeightFn;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    /// `xa`, `xb`, `ya`, `yb`
+    Pos {
+        second: bool,
+        in_b: bool,
+    },
+    Neg(Box<Expr>),
+    Op(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+static EXPRESSION: OnceLock<(String, Expr)> = OnceLock::new();
+
+/// the weight function of the expression `s`. there can only be one of them
+/// per process, so asking for a different one afterwards is an error.
+///
+/// ```
+/// # use lib::expr::expression_weight;
+/// let w = expression_weight("1 / (xa + 1) * 1 / (ya + 1)").unwrap();
+/// assert_eq!(w.at((1, 5), (3, 0)), 0.125);
+/// assert!(expression_weight("xa").is_err());
+/// ```
+pub fn expression_weight(s: &str) -> Result<WeightFn> {
+    let expr = parse(s)?;
+    let (stored, _) = EXPRESSION.get_or_init(|| (s.to_string(), expr));
+    ensure!(
+        stored == s,
+        "already using the weight {stored:?}, can't also use {s:?}"
+    );
+    Ok(WeightFn::new(stored_weight))
+}
+
+fn stored_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
+    EXPRESSION
+        .get()
+        .map_or(f64::NAN, |(_, expr)| expr.eval(x, y))
+}
+
+/// parse an expression (see the [module docs](self))
+///
+/// ```
+/// # use lib::expr::parse;
+/// let e = parse("max(xa, ya)^2 - ln(1)").unwrap();
+/// assert_eq!(e.eval((2, 0), (3, 0)), 9.0);
+/// assert!(parse("xa +").is_err());
+/// assert!(parse("xc").is_err());
+/// ```
+pub fn parse(s: &str) -> Result<Expr> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser { tokens, at: 0 };
+    let expr = parser.sum()?;
+    if let Some(t) = parser.peek() {
+        bail!("unexpected {t:?} in {s:?}");
+    }
+    Ok(expr)
+}
+
+impl Expr {
+    pub fn eval(&self, x: (usize, usize), y: (usize, usize)) -> f64 {
+        match self {
+            Expr::Num(n) => *n,
+            Expr::Pos { second, in_b } => {
+                let e = if *second { y } else { x };
+                (if *in_b { e.1 } else { e.0 }) as f64
+            }
+            Expr::Neg(e) => -e.eval(x, y),
+            Expr::Op(op, l, r) => {
+                let (l, r) = (l.eval(x, y), r.eval(x, y));
+                match op {
+                    '+' => l + r,
+                    '-' => l - r,
+                    '*' => l * r,
+                    '/' => l / r,
+                    _ => l.powf(r),
+                }
+            }
+            Expr::Call(f, args) => {
+                let args = args.iter().map(|a| a.eval(x, y)).collect::<Vec<f64>>();
+                match f.as_str() {
+                    "ln" => args[0].ln(),
+                    "exp" => args[0].exp(),
+                    "sqrt" => args[0].sqrt(),
+                    "abs" => args[0].abs(),
+                    "min" => args[0].min(args[1]),
+                    _ => args[0].max(args[1]),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Name(String),
+    Sym(char),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut num = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                num.push(d);
+                chars.next();
+            }
+            let n = num
+                .parse::<f64>()
+                .map_err(|e| anyhow!("bad number {num:?} ({e})"))?;
+            tokens.push(Token::Num(n));
+        } else if c.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_alphanumeric()) {
+                name.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Sym(c));
+            chars.next();
+        } else {
+            bail!("unexpected {c:?} in {s:?}");
+        }
+    }
+    Ok(tokens)
+}
+
+/// recursive descent, one function per precedence level
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let t = self
+            .tokens
+            .get(self.at)
+            .cloned()
+            .ok_or_else(|| anyhow!("unexpected end of the expression"))?;
+        self.at += 1;
+        Ok(t)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Sym(c));
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        ensure!(self.eat(c), "expected {c:?}");
+        Ok(())
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut l = self.product()?;
+        while let Some(op) = ['+', '-'].into_iter().find(|c| self.eat(*c)) {
+            l = Expr::Op(op, Box::new(l), Box::new(self.product()?));
+        }
+        Ok(l)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut l = self.unary()?;
+        while let Some(op) = ['*', '/'].into_iter().find(|c| self.eat(*c)) {
+            l = Expr::Op(op, Box::new(l), Box::new(self.unary()?));
+        }
+        Ok(l)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            // right associative, and binds tighter than a leading minus
+            return Ok(Expr::Op('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        match self.next()? {
+            Token::Num(n) => Ok(Expr::Num(n)),
+            Token::Sym('(') => {
+                let e = self.sum()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Token::Name(name) => match name.as_str() {
+                "xa" | "xb" | "ya" | "yb" => Ok(Expr::Pos {
+                    second: name.starts_with('y'),
+                    in_b: name.ends_with('b'),
+                }),
+                "ln" | "exp" | "sqrt" | "abs" | "min" | "max" => {
+                    let arity = if matches!(name.as_str(), "min" | "max") {
+                        2
+                    } else {
+                        1
+                    };
+                    self.expect('(')?;
+                    let mut args = vec![self.sum()?];
+                    while self.eat(',') {
+                        args.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+                    ensure!(
+                        args.len() == arity,
+                        "{name} takes {arity} argument(s), not {}",
+                        args.len()
+                    );
+                    Ok(Expr::Call(name, args))
+                }
+                _ => bail!("unknown name {name:?}"),
+            },
+            t => bail!("unexpected {t:?}"),
+        }
+    }
+}
@@ -1,20 +1,38 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::path::Path;
+
 use anyhow::Result;
+use anyhow::anyhow;
 use anyhow::bail;
 use csv::ReaderBuilder;
 use csv::StringRecord;
+use csv::Writer;
+use csv::WriterBuilder;
 use glob::glob;
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
 use itertools::Itertools;
 
+pub mod cache;
+pub mod canon;
 pub mod def;
+pub mod expr;
+pub mod rng;
+pub mod rwt;
+pub mod shard;
+pub mod shrink;
+pub mod stream;
 pub mod tau_w;
 pub mod weights;
 
 pub const PRECISION: f64 = 1e-6f64;
 pub const CHUNK_SIZE: usize = 512;
 
-#[derive(Debug, Clone, serde_derive::Deserialize, PartialEq, Eq)]
+#[derive(
+    Debug, Clone, serde_derive::Serialize, serde_derive::Deserialize, PartialEq, Eq, Hash,
+)]
 pub struct RankingsCsvRow {
     pub a: String,
     pub b: String,
@@ -28,6 +46,10 @@ pub struct AlgoOutputRow {
     pub tmax: f64,
     pub pmin: String,
     pub pmax: String,
+    /// how many optimal arbitrations there are, which can be more than the
+    /// ones listed
+    pub nmin: Option<u128>,
+    pub nmax: Option<u128>,
 }
 
 #[derive(Debug, Default)]
@@ -36,6 +58,13 @@ pub struct AlgoOut {
     pub tmax: Option<f64>,
     pub minp: Vec<(String, String)>,
     pub maxp: Vec<(String, String)>,
+    /// how many optimal arbitrations there are, if the algorithm counted them
+    pub nmin: Option<u128>,
+    pub nmax: Option<u128>,
+    /// certificates of optimality, if the algorithm gave any: the pairs (of
+    /// names) that go against the bound
+    pub cmin: Option<Vec<(String, String)>>,
+    pub cmax: Option<Vec<(String, String)>>,
 }
 
 pub fn parse_row(row: &StringRecord) -> Result<RankingsCsvRow> {
@@ -47,17 +76,7 @@ pub fn read_glob_csv(g: &str, header: Vec<&str>) -> Result<Vec<StringRecord>> {
     let mut rows = vec![];
 
     for src in glob(g)? {
-        let path = src?;
-        let mut csv_read = ReaderBuilder::new().has_headers(true).from_path(&path)?;
-
-        // check header
-        if !header.is_empty() && csv_read.headers()? != header {
-            bail!(
-                "Incompatible CSV data ({}): expected header {header:?}, got {:?}",
-                path.display(),
-                csv_read.headers()?.iter().collect_vec()
-            );
-        }
+        let mut csv_read = open_csv(&src?, &header)?;
 
         // iterate over data
         rows.append(&mut csv_read.records().collect::<Result<Vec<_>, csv::Error>>()?);
@@ -68,6 +87,94 @@ pub fn read_glob_csv(g: &str, header: Vec<&str>) -> Result<Vec<StringRecord>> {
     Ok(rows)
 }
 
+/// a reader of the csv file at `path`, whose columns start with `header`
+pub fn open_csv(path: &Path, header: &[&str]) -> Result<csv::Reader<File>> {
+    let mut csv_read = ReaderBuilder::new().has_headers(true).from_path(path)?;
+
+    // check header. there can be more columns after the ones we need
+    // (such as the seed a row was generated with)
+    let found = csv_read.headers()?.clone();
+    if found.len() < header.len() || found.iter().zip(header).any(|(f, h)| f != *h) {
+        bail!(
+            "Incompatible CSV data ({}): expected header {header:?}, got {:?}",
+            path.display(),
+            found.iter().collect_vec()
+        );
+    }
+    Ok(csv_read)
+}
+
+/// a writer that appends to the output at `path`, and the inputs (its `a` and
+/// `b` columns) that already have a row there. a last row that was only half
+/// written (the run was killed while writing it) is cut off. with `overwrite`
+/// it starts over instead.
+///
+/// ```
+/// # use lib::resume_csv;
+/// let path = std::env::temp_dir().join(format!("resume-doc-{}.csv", std::process::id()));
+/// std::fs::write(&path, "a,b,t\nx y,y x,-1\nx y,x y,0.").unwrap();
+/// let (mut writer, done) = resume_csv(&path, false).unwrap();
+/// assert_eq!(done.len(), 1);
+/// writer.write_record(["x y", "x y", "1"]).unwrap();
+/// writer.flush().unwrap();
+/// let written = std::fs::read_to_string(&path).unwrap();
+/// assert_eq!(written, "a,b,t\nx y,y x,-1\nx y,x y,1\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn resume_csv(
+    path: &Path,
+    overwrite: bool,
+) -> Result<(Writer<File>, HashSet<RankingsCsvRow>)> {
+    let mut done = HashSet::new();
+    if overwrite || !path.is_file() {
+        return Ok((Writer::from_path(path)?, done));
+    }
+
+    let content = std::fs::read(path)?;
+    let complete = content
+        .iter()
+        .rposition(|c| *c == b'\n')
+        .map_or(0, |i| i + 1);
+    if complete < content.len() {
+        println!(
+            "cutting off the half written last row of {}",
+            path.display()
+        );
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(complete as u64)?;
+    }
+
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            anyhow!(
+                "can't continue {}: it has no {name:?} column",
+                path.display()
+            )
+        })
+    };
+    if !headers.is_empty() {
+        let (a, b) = (column("a")?, column("b")?);
+        for record in reader.records() {
+            let record = record?;
+            done.insert(RankingsCsvRow {
+                a: record[a].to_string(),
+                b: record[b].to_string(),
+            });
+        }
+    }
+
+    // only write a header if there isn't one yet
+    let file = OpenOptions::new().append(true).open(path)?;
+    let writer = WriterBuilder::new()
+        .has_headers(headers.is_empty())
+        .from_writer(file);
+    Ok((writer, done))
+}
+
 pub fn progress_bar(n: u64) -> Result<ProgressBar> {
     Ok(
         ProgressBar::new(n).with_style(ProgressStyle::default_bar().template(
@@ -76,8 +183,14 @@ pub fn progress_bar(n: u64) -> Result<ProgressBar> {
     )
 }
 
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::os::unix::process::ExitStatusExt;
 use std::path::PathBuf;
 use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::mpsc::RecvTimeoutError;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -85,26 +198,229 @@ pub trait Case {
     fn algo_args(&self) -> Vec<String>;
 }
 
-pub fn run_solver_on<C: Case>(algo: &PathBuf, inp: C) -> Result<(String, Duration)> {
+/// limits on a single run of a solver. the process is killed when it goes over
+/// them.
+#[derive(Debug, Clone, Copy, Default, clap_derive::Args)]
+pub struct Limits {
+    /// wall-clock limit per case, in seconds
+    #[arg(long = "timeout", value_parser = parse_secs)]
+    pub time: Option<Duration>,
+    /// memory (address space) limit per case, in MiB
+    #[arg(long = "memory")]
+    pub memory_mib: Option<u64>,
+}
+
+fn parse_secs(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .map_err(|e| e.to_string())
+        .and_then(|x| Duration::try_from_secs_f64(x).map_err(|e| e.to_string()))
+}
+
+/// how a run of a solver ended
+#[derive(Debug, Clone)]
+pub enum RunStatus {
+    /// exited successfully, with this output
+    Done(String),
+    /// killed for going over the time limit
+    Timeout,
+    /// ran out of the memory it was allowed
+    ResourceExceeded,
+}
+
+impl RunStatus {
+    /// what the status is called in outputs
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunStatus::Done(_) => "done",
+            RunStatus::Timeout => "timeout",
+            RunStatus::ResourceExceeded => "resource_exceeded",
+        }
+    }
+}
+
+/// what a run of a solver cost, besides wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    /// user + system time
+    pub cpu: Duration,
+    /// peak resident set size, in KiB
+    pub max_rss: u64,
+}
+
+/// run `algo` on `inp` within `limits`. a process that fails for any other
+/// reason than the limits is an error.
+pub fn run_solver_on<C: Case>(
+    algo: &PathBuf,
+    inp: C,
+    limits: Limits,
+) -> Result<(RunStatus, Duration, Usage)> {
     let mut cmd = Command::new(algo);
 
     for arg in inp.algo_args() {
         cmd.arg(arg);
     }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // so that a timeout also kills whatever it started
+        .process_group(0);
+
+    if let Some(mib) = limits.memory_mib {
+        let bytes = mib.saturating_mul(1 << 20) as libc::rlim_t;
+        // SAFETY: only calls setrlimit, which is async-signal-safe, between fork
+        // and exec
+        unsafe {
+            cmd.pre_exec(move || {
+                let lim = libc::rlimit {
+                    rlim_cur: bytes,
+                    rlim_max: bytes,
+                };
+                if libc::setrlimit(libc::RLIMIT_AS, &lim) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
 
     let start = Instant::now();
 
-    let out = cmd.output()?;
+    let mut child = cmd.spawn()?;
+    // read the pipes while waiting, or a chatty child blocks on a full pipe
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let out_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+    let err_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        stderr.read_to_end(&mut buf).map(|_| buf)
+    });
 
-    if !out.status.success() {
-        bail!(
-            "process failed: {}. stderr: {}",
-            out.status,
-            String::from_utf8_lossy(&out.stderr)
-        )
+    let pid = child.id() as libc::pid_t;
+    // the time limit is kept by another thread, so this one can block until
+    // the child exits, and time it exactly
+    let (exited, exited_rx) = std::sync::mpsc::channel::<()>();
+    let killer = limits.time.map(|t| {
+        let left = t.saturating_sub(start.elapsed());
+        std::thread::spawn(move || match exited_rx.recv_timeout(left) {
+            Err(RecvTimeoutError::Timeout) => {
+                // SAFETY: the child leads its own process group, nothing else
+                // is in it. it isn't reaped yet (see below), so its pid can't
+                // have been reused
+                unsafe { libc::kill(-pid, libc::SIGKILL) };
+                true
+            }
+            _ => false,
+        })
+    });
+
+    // wait for it to exit, but leave it a zombie until the killer is done
+    loop {
+        // SAFETY: siginfo is plain old data, and pid is our own child, which
+        // nothing else waits for
+        let r = unsafe {
+            let mut info: libc::siginfo_t = std::mem::zeroed();
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if r == 0 {
+            break;
+        }
+        let e = std::io::Error::last_os_error();
+        if e.kind() != std::io::ErrorKind::Interrupted {
+            // don't leave it running, or the killer waiting for it
+            // SAFETY: as for the killer, it isn't reaped yet
+            unsafe { libc::kill(-pid, libc::SIGKILL) };
+            let _ = exited.send(());
+            if let Some(killer) = killer {
+                let _ = killer.join();
+            }
+            let _ = child.wait();
+            bail!("waitid failed: {e}");
+        }
+    }
+    let elapsed = start.elapsed();
+    // it doesn't matter if the killer is already gone
+    let _ = exited.send(());
+    let killed = match killer {
+        Some(killer) => killer
+            .join()
+            .map_err(|_| anyhow!("the timeout thread panicked"))?,
+        None => false,
+    };
+
+    let mut status = 0;
+    // SAFETY: rusage is plain old data
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: as above. it has exited, so this returns right away
+    if unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) } != pid {
+        bail!("wait4 failed: {}", std::io::Error::last_os_error());
+    }
+
+    let usage = Usage {
+        cpu: timeval(rusage.ru_utime) + timeval(rusage.ru_stime),
+        max_rss: rusage.ru_maxrss as u64,
+    };
+    let output = out_reader
+        .join()
+        .map_err(|_| anyhow!("stdout reader panicked"))??;
+    let errors = err_reader
+        .join()
+        .map_err(|_| anyhow!("stderr reader panicked"))??;
+
+    // the killer can fire just after the child exited on its own, when the kill
+    // does nothing: only a child that died of it timed out
+    let status = ExitStatus::from_raw(status);
+    if killed && status.signal() == Some(libc::SIGKILL) {
+        return Ok((RunStatus::Timeout, elapsed, usage));
+    }
+
+    if !status.success() {
+        let errors = String::from_utf8_lossy(&errors);
+        // under the limit, a failed allocation either aborts it or makes it
+        // exit with a complaint. any other signal (a segfault, say) is a crash
+        if limits.memory_mib.is_some()
+            && status.signal().is_none_or(|s| s == libc::SIGABRT)
+            && failed_allocation(&errors)
+        {
+            return Ok((RunStatus::ResourceExceeded, elapsed, usage));
+        }
+        bail!("process failed: {status}. stderr: {errors}")
     }
 
-    Ok((String::from_utf8(out.stdout)?, start.elapsed()))
+    Ok((RunStatus::Done(String::from_utf8(output)?), elapsed, usage))
+}
+
+/// does the stderr of a process say it couldn't allocate memory? rust's
+/// allocator says "memory allocation of n bytes failed" before it aborts, c++
+/// throws `std::bad_alloc`, and `ENOMEM` reads "cannot allocate memory".
+///
+/// ```
+/// # use lib::failed_allocation;
+/// assert!(failed_allocation("memory allocation of 4096 bytes failed\n"));
+/// assert!(failed_allocation("terminate called after throwing an instance of 'std::bad_alloc'"));
+/// assert!(!failed_allocation("thread 'main' panicked: index out of bounds\n"));
+/// ```
+pub fn failed_allocation(errors: &str) -> bool {
+    let errors = errors.to_lowercase();
+    [
+        "memory allocation of",
+        "bad_alloc",
+        "cannot allocate memory",
+        "out of memory",
+    ]
+    .iter()
+    .any(|m| errors.contains(m))
+}
+
+fn timeval(t: libc::timeval) -> Duration {
+    Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64)
 }
 
 impl Case for &&RankingsCsvRow {
@@ -131,7 +447,8 @@ impl PartialEq for AlgoOut {
         // we can't really compare the permutations returned by each algorithm:
         // there are possibly many many optimal solutions,
         // and the algorithms don't need to return the same ones.
-        // we trust that the tau values weren't pulled out of thin air
+        // that each one is valid and achieves its tau is checked on its own,
+        // see `verifier::compare::invalid_arbitration`
         true
     }
 }
@@ -0,0 +1,29 @@
+//! put the outputs of a sharded run together (see [`lib::shard`])
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clap_derive::Parser;
+use lib::shard::merge;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// the output the shards were given
+    pub output: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let merged = merge(&args.output)?;
+    println!(
+        "merged {} shards into {} rows of {}",
+        merged.shards,
+        merged.rows,
+        args.output.display()
+    );
+    if merged.over_limits > 0 {
+        println!("{} cases went over the limits", merged.over_limits);
+    }
+    Ok(())
+}
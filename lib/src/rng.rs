@@ -0,0 +1,36 @@
+//! seeded randomness, for generating data and test inputs
+
+/// a small, seeded pseudo random generator (splitmix64). good enough to make
+/// up rankings, and the same everywhere, so a seed is all it takes to make the
+/// same data again.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// uniform in `0..n`, for `n > 0`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// uniform in `[0, 1)`
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// true with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.uniform() < p
+    }
+}
@@ -0,0 +1,160 @@
+//! # integer representation of a ranking with ties
+//! - one u64 has 16 * 4 bits with indices 0-15
+//! - the index of the 4 bit number within the u64 corresponds to the index of
+//!   an element (see [`element_from_index`]), which gives us its name (char)
+//! - the value of the 4 bit number is the rank of the element: the index of
+//!   its tie group
+//!
+//! a ranking of `n` elements has one code whose ranks are exactly `0..k` for
+//! some `k`, which we call canonical. going over the canonical codes goes over
+//! every ranking with ties (weak order) of `n` elements, of which there are the
+//! fubini numbers 1, 3, 13, 75, 541, 4683, 47293, ...
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::ensure;
+use itertools::Itertools;
+
+use crate::def::*;
+
+/// the most elements a code has room for
+pub const MAX_ELEMENTS: usize = 16;
+
+/// the ranking of the first `n` elements that the code `i` describes. ranks no
+/// element has are skipped.
+///
+/// ```
+/// # use lib::def::partial_to_string;
+/// # use lib::rwt::int_to_rwt;
+/// // ranks 1, 0, 1 of a, b, c
+/// assert_eq!(partial_to_string(&int_to_rwt(0x101, 3)), "b (a c)");
+/// assert_eq!(partial_to_string(&int_to_rwt(0x505, 3)), "b (a c)");
+/// ```
+pub fn int_to_rwt(i: u64, n: usize) -> PartialOrder {
+    debug_assert!(n <= MAX_ELEMENTS);
+    (0..n)
+        .map(|k| (rank_of(i, k), element_from_index(k)))
+        .sorted()
+        .chunk_by(|(rank, _)| *rank)
+        .into_iter()
+        .map(|(_, tg)| tg.map(|(_, e)| e).collect())
+        .collect()
+}
+
+/// the canonical code of a ranking of the first (up to 16) elements
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::rwt::int_to_rwt;
+/// # use lib::rwt::rwt_to_int;
+/// let r = partial_from_string("(1 3) 2 4", &mut BTreeMap::new()).unwrap();
+/// let i = rwt_to_int(&r).unwrap();
+/// // 1 and 3 are a and b, tied first
+/// assert_eq!(i, 0x2100);
+/// assert_eq!(int_to_rwt(i, 4), r);
+/// ```
+pub fn rwt_to_int(r: &PartialOrder) -> Result<u64> {
+    let n = r.set_size();
+    ensure!(
+        n <= MAX_ELEMENTS,
+        "only rankings of up to {MAX_ELEMENTS} elements have a code, not {n}"
+    );
+    let mut i = 0;
+    for (rank, tg) in r.iter().enumerate() {
+        for e in tg {
+            let k = (0..n)
+                .find(|k| element_from_index(*k) == *e)
+                .ok_or_else(|| anyhow!("{e} isn't one of the first {n} elements"))?;
+            i |= (rank as u64) << (4 * k);
+        }
+    }
+    Ok(i)
+}
+
+/// the canonical codes of all rankings with ties of `n` elements, in
+/// increasing order. goes over all `n^n` codes with ranks below `n`, so it's
+/// only meant for small `n`.
+///
+/// ```
+/// # use lib::rwt::weak_orders;
+/// let counts = (1..=6).map(|n| weak_orders(n).count());
+/// assert_eq!(counts.collect::<Vec<_>>(), vec![1, 3, 13, 75, 541, 4683]);
+/// ```
+pub fn weak_orders(n: usize) -> impl Iterator<Item = u64> {
+    debug_assert!((1..=MAX_ELEMENTS).contains(&n));
+    (0..n)
+        .map(|_| 0..n as u64)
+        .multi_cartesian_product()
+        .map(|ranks| ranks.iter().rev().fold(0, |i, r| (i << 4) | r))
+        .filter(move |i| is_canonical(*i, n))
+}
+
+/// every pair of rankings of `n` elements as the solvers see them: elements
+/// are named in the order they first appear in a (see [`partial_from_string`]),
+/// so a only decides where its ties are, and b can be any ranking with ties.
+///
+/// ```
+/// # use lib::rwt::all_pairs;
+/// // 4 tie structures of a, 13 rankings b
+/// assert_eq!(all_pairs(3).count(), 4 * 13);
+/// ```
+pub fn all_pairs(n: usize) -> impl Iterator<Item = (PartialOrder, PartialOrder)> {
+    let bs = weak_orders(n).collect::<Vec<u64>>();
+    tie_structures(n).flat_map(move |a| {
+        bs.clone()
+            .into_iter()
+            .map(move |b| (int_to_rwt(a, n), int_to_rwt(b, n)))
+    })
+}
+
+/// every pair of rankings of `n` elements, up to renaming the elements: two
+/// pairs are the same if one becomes the other by renaming. such a pair only
+/// depends on how many elements every tie group of a shares with every tie
+/// group of b, so we take the one where the elements of each tie group of a
+/// are in order of their rank in b.
+///
+/// ```
+/// # use lib::rwt::distinct_pairs;
+/// // (a b)/(a b), (a b)/a b, a b/(a b), a b/a b, a b/b a
+/// assert_eq!(distinct_pairs(2).count(), 5);
+/// ```
+pub fn distinct_pairs(n: usize) -> impl Iterator<Item = (PartialOrder, PartialOrder)> {
+    all_pairs(n).filter(|(a, b)| {
+        let ranks_b = b
+            .iter()
+            .enumerate()
+            .flat_map(|(rank, tg)| tg.iter().map(move |e| (*e, rank)))
+            .collect::<BTreeMap<Element, usize>>();
+        a.iter().all(|tg| tg.iter().map(|e| ranks_b[e]).is_sorted())
+    })
+}
+
+/// the codes of the rankings that keep the elements in order, so only differ
+/// in where their ties are
+fn tie_structures(n: usize) -> impl Iterator<Item = u64> {
+    (0..(1u64 << n.saturating_sub(1))).map(move |cuts| {
+        // bit k set: element k + 1 starts a new tie group
+        let mut i = 0;
+        let mut rank = 0;
+        for k in 1..n {
+            rank += (cuts >> (k - 1)) & 1;
+            i |= rank << (4 * k);
+        }
+        i
+    })
+}
+
+fn rank_of(i: u64, k: usize) -> u64 {
+    (i >> (4 * k)) & 0xF
+}
+
+/// are the ranks of the first `n` elements exactly `0..k` for some `k`?
+fn is_canonical(i: u64, n: usize) -> bool {
+    let mut used = 0u16;
+    for k in 0..n {
+        used |= 1 << rank_of(i, k);
+    }
+    used & used.wrapping_add(1) == 0
+}
@@ -0,0 +1,301 @@
+//! splitting a run over several processes, and putting it back together
+//!
+//! shard `i/n` (counting from 1) takes the `i`-th of `n` consecutive slices of
+//! the cases, after they're deduplicated and sorted, and writes next to the
+//! output with `.shard-i-of-n` in its name. a shard that's done leaves a
+//! `.done` file next to that, with how many cases and rows it had and how many
+//! of the cases went over the limits. the rows of all of them in order are the
+//! rows of a single run, which is what [`merge`] puts together.
+//!
+//! a streamed run (see [`crate::stream`]) can't be sharded.
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use csv::ReaderBuilder;
+use csv::Writer;
+
+use crate::RankingsCsvRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// from 1 to `n`
+    pub i: usize,
+    pub n: usize,
+}
+
+/// what [`merge`] put together
+#[derive(Debug)]
+pub struct Merged {
+    pub shards: usize,
+    pub rows: usize,
+    pub over_limits: usize,
+}
+
+/// what a shard that's done says about itself, in its `.done` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Done {
+    pub cases: usize,
+    pub rows: usize,
+    pub over_limits: usize,
+}
+
+impl Shard {
+    /// the indices of the cases of this shard, out of `len`
+    ///
+    /// ```
+    /// # use lib::shard::Shard;
+    /// let shards = (1..=3).map(|i| Shard { i, n: 3 }.range(10));
+    /// assert_eq!(shards.collect::<Vec<_>>(), vec![0..3, 3..6, 6..10]);
+    /// ```
+    pub fn range(&self, len: usize) -> Range<usize> {
+        (self.i - 1) * len / self.n..self.i * len / self.n
+    }
+
+    /// keep only the cases of this shard
+    pub fn select<T>(&self, cases: &mut Vec<T>) {
+        let range = self.range(cases.len());
+        cases.truncate(range.end);
+        cases.drain(..range.start);
+    }
+
+    /// where this shard writes the output that would be at `output`
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use lib::shard::Shard;
+    /// let p = Shard { i: 2, n: 4 }.path(Path::new("out/eval.csv"));
+    /// assert_eq!(p, Path::new("out/eval.shard-2-of-4.csv"));
+    /// ```
+    pub fn path(&self, output: &Path) -> PathBuf {
+        let stem = output
+            .file_stem()
+            .map_or(String::new(), |s| s.to_string_lossy().to_string());
+        let ext = output
+            .extension()
+            .map_or(String::new(), |e| format!(".{}", e.to_string_lossy()));
+        output.with_file_name(format!("{stem}.shard-{}-of-{}{ext}", self.i, self.n))
+    }
+
+    /// forget that the shard at `output` was done, before it writes to it again
+    pub fn start(&self, output: &Path) -> Result<()> {
+        match std::fs::remove_file(done_path(&self.path(output))) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// mark the shard at `output` as done, with how many cases it had and how
+    /// many of them went over the limits. the rows are counted in its output.
+    ///
+    /// ```
+    /// # use lib::shard::Done;
+    /// # use lib::shard::Shard;
+    /// # use lib::shard::merge;
+    /// let dir = std::env::temp_dir().join(format!("shard-doc-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let output = dir.join("eval.csv");
+    /// let shard = Shard { i: 1, n: 1 };
+    /// std::fs::write(shard.path(&output), "a,b\nx y,y x\n").unwrap();
+    /// shard.finish(&output, 2, 1).unwrap();
+    /// assert_eq!(shard.done(&output).unwrap(), Some(Done { cases: 2, rows: 1, over_limits: 1 }));
+    /// assert_eq!(merge(&output).unwrap().over_limits, 1);
+    ///
+    /// // a row more than it said it had
+    /// std::fs::write(shard.path(&output), "a,b\nx y,y x\nx y,x y\n").unwrap();
+    /// assert!(merge(&output).is_err());
+    /// shard.start(&output).unwrap();
+    /// assert_eq!(shard.done(&output).unwrap(), None);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn finish(&self, output: &Path, cases: usize, over_limits: usize) -> Result<()> {
+        let path = self.path(output);
+        let rows = ReaderBuilder::new()
+            .has_headers(true)
+            .from_path(&path)?
+            .records()
+            .count();
+        std::fs::write(
+            done_path(&path),
+            format!("cases,rows,over_limits\n{cases},{rows},{over_limits}\n"),
+        )?;
+        Ok(())
+    }
+
+    /// what the shard at `output` said when it was done, if it is
+    pub fn done(&self, output: &Path) -> Result<Option<Done>> {
+        let path = done_path(&self.path(output));
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let counts = content.lines().nth(1).map(|line| {
+            line.split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<usize>, _>>()
+        });
+        match counts {
+            Some(Ok(c)) if c.len() == 3 => Ok(Some(Done {
+                cases: c[0],
+                rows: c[1],
+                over_limits: c[2],
+            })),
+            _ => bail!("{} isn't cases,rows,over_limits", path.display()),
+        }
+    }
+}
+
+/// the output of a run, or of its shard if there is one
+pub fn output_path(output: &Path, shard: Option<Shard>) -> PathBuf {
+    shard.map_or(output.to_path_buf(), |s| s.path(output))
+}
+
+/// put the shards of `output` together into it. every shard from 1 to `n` has
+/// to be there and done, and no input can have rows in two of them.
+pub fn merge(output: &Path) -> Result<Merged> {
+    let n = find_shard_count(output)?;
+
+    let mut writer = Writer::from_path(output)?;
+    let mut header = None;
+    let mut seen = HashSet::new();
+    let mut rows = 0;
+    let mut over_limits = 0;
+    for i in 1..=n {
+        let shard = Shard { i, n };
+        let path = shard.path(output);
+        ensure!(
+            path.is_file(),
+            "shard {i}/{n} is missing ({})",
+            path.display()
+        );
+        let done = shard
+            .done(output)?
+            .ok_or_else(|| anyhow!("shard {i}/{n} isn't done ({})", path.display()))?;
+        over_limits += done.over_limits;
+        let shard_rows = rows;
+
+        let mut reader = ReaderBuilder::new().has_headers(true).from_path(&path)?;
+        let headers = reader.headers()?.clone();
+        match &header {
+            None => {
+                writer.write_record(&headers)?;
+                header = Some(headers.clone());
+            }
+            Some(h) => ensure!(
+                *h == headers,
+                "shard {i}/{n} has other columns than shard 1: {:?}",
+                headers.iter().collect::<Vec<_>>()
+            ),
+        }
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| anyhow!("shard {i}/{n} has no {name:?} column"))
+        };
+        let (a, b) = (column("a")?, column("b")?);
+
+        for record in reader.records() {
+            let record = record?;
+            let input = RankingsCsvRow {
+                a: record[a].to_string(),
+                b: record[b].to_string(),
+            };
+            if !seen.insert(input.clone()) {
+                bail!(
+                    "{} / {} has a row in more than one shard (again in {i}/{n})",
+                    input.a,
+                    input.b
+                );
+            }
+            writer.write_record(&record)?;
+            rows += 1;
+        }
+        ensure!(
+            rows - shard_rows == done.rows,
+            "shard {i}/{n} has {} rows, but it was done with {} ({})",
+            rows - shard_rows,
+            done.rows,
+            path.display()
+        );
+    }
+    writer.flush()?;
+    Ok(Merged {
+        shards: n,
+        rows,
+        over_limits,
+    })
+}
+
+/// how many shards the output was split into, from the names of the ones there
+fn find_shard_count(output: &Path) -> Result<usize> {
+    let dir = match output.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    // the name of shard i/n, with the i-of-n cut out
+    let pattern = Shard { i: 0, n: 0 }.path(output);
+    let pattern = pattern
+        .file_name()
+        .map_or(String::new(), |f| f.to_string_lossy().to_string());
+    let (prefix, suffix) = pattern.split_once("0-of-0").expect("it's in the name");
+
+    let mut counts = HashSet::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        let split = name
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .and_then(|rest| rest.split_once("-of-"));
+        if let Some((i, n)) = split
+            && i.parse::<usize>().is_ok()
+            && let Ok(n) = n.parse::<usize>()
+        {
+            counts.insert(n);
+        }
+    }
+    match counts.len() {
+        0 => bail!("no shards of {} in {}", output.display(), dir.display()),
+        1 => Ok(counts.into_iter().next().expect("one count")),
+        _ => bail!(
+            "shards of {} from different splits: {counts:?}",
+            output.display()
+        ),
+    }
+}
+
+fn done_path(shard_output: &Path) -> PathBuf {
+    let mut name = shard_output.as_os_str().to_owned();
+    name.push(".done");
+    PathBuf::from(name)
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (i, n) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("a shard is i/n, like 1/4, not {s:?}"))?;
+        let i = i.parse::<usize>()?;
+        let n = n.parse::<usize>()?;
+        ensure!(
+            1 <= i && i <= n,
+            "shard {i}/{n} isn't one of 1/{n} to {n}/{n}"
+        );
+        Ok(Shard { i, n })
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.i, self.n)
+    }
+}
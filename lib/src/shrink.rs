@@ -0,0 +1,131 @@
+//! shrinking failing inputs
+//!
+//! a delta-debugging loop over an input: try to remove chunks of items (from
+//! half of them down to single ones, always keeping a pair), to split an item
+//! out of its tie group, or to merge two neighbouring tie groups, and keep the
+//! first change after which the input still fails, until none of them do.
+//!
+//! a change is only kept if it makes the input smaller: fewer items, or else
+//! fewer tie groups (of more than one item), or else fewer linear extensions.
+//! every split does, and so does merging two tie groups that both have ties, so
+//! the loop ends. removing items is tried first.
+
+use anyhow::Result;
+
+use crate::def::*;
+
+/// shrink the input `a`,`b` for as long as `still_fails` holds, and return the
+/// smallest input it got to. `still_fails` should hold for the input itself.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::shrink::shrink_orders;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("1 (2 3 4) 5", &mut map).unwrap();
+/// let b = partial_from_string("(1 2) 5 4 3", &mut map).unwrap();
+/// // fails whenever c and d are tied in a
+/// let fails = |a: &Vec<Vec<char>>, _: &Vec<Vec<char>>| {
+///     Ok(a.iter().any(|tg| tg.contains(&'c') && tg.contains(&'d')))
+/// };
+/// let (sa, sb) = shrink_orders(&a, &b, fails).unwrap();
+/// assert_eq!(sa, vec![vec!['c', 'd']]);
+/// assert_eq!(sb, vec![vec!['d'], vec!['c']]);
+/// // but never to less than a pair
+/// let (sa, _) = shrink_orders(&a, &b, |_, _| Ok(true)).unwrap();
+/// assert_eq!(sa.concat().len(), 2);
+/// ```
+pub fn shrink_orders<F>(
+    a: &PartialOrder,
+    b: &PartialOrder,
+    mut still_fails: F,
+) -> Result<(PartialOrder, PartialOrder)>
+where
+    F: FnMut(&PartialOrder, &PartialOrder) -> Result<bool>,
+{
+    let (mut rank_a, mut rank_b) = (a.clone(), b.clone());
+    'shrinking: loop {
+        let current = size(&rank_a, &rank_b);
+        for (ca, cb) in candidates(&rank_a, &rank_b) {
+            if size(&ca, &cb) < current && still_fails(&ca, &cb)? {
+                (rank_a, rank_b) = (ca, cb);
+                continue 'shrinking;
+            }
+        }
+        return Ok((rank_a, rank_b));
+    }
+}
+
+/// the inputs one step smaller than `rank_a`,`rank_b`, roughly from the
+/// biggest step to the smallest.
+fn candidates(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+) -> Vec<(PartialOrder, PartialOrder)> {
+    let mut out = Vec::new();
+    let items = rank_a.concat();
+
+    // remove chunks of items, keeping at least two (so there's a pair)
+    let mut size = items.len() / 2;
+    while size > 0 {
+        for chunk in items.chunks(size).filter(|c| c.len() + 2 <= items.len()) {
+            out.push((without(rank_a, chunk), without(rank_b, chunk)));
+        }
+        size /= 2;
+    }
+
+    // split an item out of its tie group, or merge two neighbouring ones
+    out.extend(regroupings(rank_a).map(|r| (r, rank_b.clone())));
+    out.extend(regroupings(rank_b).map(|r| (rank_a.clone(), r)));
+
+    out
+}
+
+/// `rank` with one item split out of its tie group (right after it), or with
+/// two neighbouring tie groups merged.
+fn regroupings(rank: &PartialOrder) -> impl Iterator<Item = PartialOrder> {
+    let splits = rank
+        .iter()
+        .enumerate()
+        .filter(|(_, tg)| tg.len() > 1)
+        .flat_map(|(g, tg)| (0..tg.len()).map(move |k| (g, k)))
+        .map(|(g, k)| {
+            let mut r = rank.clone();
+            let e = r[g].remove(k);
+            r.insert(g + 1, vec![e]);
+            r
+        });
+    let merges = (1..rank.len()).map(|g| {
+        let mut r = rank.clone();
+        let next = r.remove(g);
+        r[g - 1].extend(next);
+        r
+    });
+    splits.chain(merges)
+}
+
+/// how big an input is, compared in this order
+fn size(rank_a: &PartialOrder, rank_b: &PartialOrder) -> (usize, usize, u128) {
+    let tied = rank_a
+        .iter()
+        .chain(rank_b)
+        .filter(|tg| tg.len() > 1)
+        .count();
+    let extensions = rank_a
+        .linear_ext_count()
+        .saturating_mul(rank_b.linear_ext_count());
+    (rank_a.set_size(), tied, extensions)
+}
+
+/// `rank` without the items in `removed`
+fn without(rank: &PartialOrder, removed: &[Element]) -> PartialOrder {
+    rank.iter()
+        .map(|tg| {
+            tg.iter()
+                .filter(|e| !removed.contains(e))
+                .copied()
+                .collect::<TieGroup>()
+        })
+        .filter(|tg| !tg.is_empty())
+        .collect()
+}
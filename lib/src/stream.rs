@@ -0,0 +1,220 @@
+//! going through inputs that don't fit in memory
+//!
+//! [`read_glob_csv`](crate::read_glob_csv) has every row in memory at once, so
+//! that the cases can be deduplicated and sorted. a stream instead reads a chunk
+//! of rows at a time, on its own thread, while the chunk before it is solved:
+//! [`pipeline`] only reads a few chunks ahead, so memory stays the same however
+//! big the input is. the price is that the cases stay in the order of the
+//! input, and only duplicates within a chunk are dropped. for the same reason a
+//! stream can't be sharded (see [`crate::shard`]): its shards would be slices
+//! of the raw rows, with the same input in more than one of them.
+//!
+//! since the order stays the same, a streamed output is continued from how far
+//! into the input it got (see [`resume_stream`]), instead of from the inputs in
+//! it.
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use csv::Writer;
+use csv::WriterBuilder;
+use glob::glob;
+
+use crate::RankingsCsvRow;
+use crate::open_csv;
+use crate::parse_row;
+
+/// how many rows the csv files matching `g` have, without keeping them
+pub fn count_rows(g: &str, header: &[&str]) -> Result<usize> {
+    let mut count = 0;
+    for path in glob(g)? {
+        let mut reader = open_csv(&path?, header)?;
+        let mut record = csv::StringRecord::new();
+        while reader.read_record(&mut record)? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// the rows of the csv files matching `g` (counted over all of them) in
+/// `rows`, `chunk` at a time. files are only opened once they're got to.
+pub fn stream_rows(
+    g: &str,
+    header: &[&str],
+    rows: Range<usize>,
+    chunk: usize,
+) -> Result<impl Iterator<Item = Result<Vec<RankingsCsvRow>>> + Send> {
+    let paths = glob(g)?.collect::<Result<Vec<PathBuf>, _>>()?;
+    let header = header
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<String>>();
+    let mut records = paths
+        .into_iter()
+        .flat_map(move |path| {
+            let header = header.iter().map(String::as_str).collect::<Vec<&str>>();
+            let records: Box<dyn Iterator<Item = Result<RankingsCsvRow>> + Send> =
+                match open_csv(&path, &header) {
+                    Ok(reader) => Box::new(reader.into_records().map(|r| {
+                        r.map_err(anyhow::Error::from).and_then(|r| parse_row(&r))
+                    })),
+                    Err(e) => Box::new(std::iter::once(Err(e))),
+                };
+            records
+        })
+        .skip(rows.start)
+        .take(rows.len());
+
+    Ok(std::iter::from_fn(move || {
+        let group = records.by_ref().take(chunk).collect::<Result<Vec<_>>>();
+        match group {
+            Ok(group) if group.is_empty() => None,
+            group => Some(group),
+        }
+    }))
+}
+
+/// how far a streamed run got, kept next to its output after every chunk: the
+/// rows of the input it went through, and how long the output was then
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Checkpoint {
+    pub rows: usize,
+    pub len: u64,
+}
+
+/// a writer that appends to the streamed output at `path`, and how many rows of
+/// the input it already went through. the output is cut back to its last
+/// [`checkpoint`], so a chunk that was written but not checkpointed (the run
+/// was killed) is done again. with `overwrite` it starts over instead.
+///
+/// ```
+/// # use lib::stream::checkpoint;
+/// # use lib::stream::resume_stream;
+/// let path = std::env::temp_dir().join(format!("stream-doc-{}.csv", std::process::id()));
+/// let (mut writer, _) = resume_stream(&path, true).unwrap();
+/// writer.write_record(["a", "b"]).unwrap();
+/// writer.write_record(["x y", "y x"]).unwrap();
+/// writer.flush().unwrap();
+/// checkpoint(&path, 3).unwrap();
+/// // killed before the next checkpoint
+/// writer.write_record(["x y", "x y"]).unwrap();
+/// writer.flush().unwrap();
+///
+/// let (_, skip) = resume_stream(&path, false).unwrap();
+/// assert_eq!(skip, 3);
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "a,b\nx y,y x\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// # std::fs::remove_file(lib::stream::checkpoint_path(&path)).unwrap();
+/// ```
+pub fn resume_stream(path: &Path, overwrite: bool) -> Result<(Writer<File>, usize)> {
+    let saved = checkpoint_path(path);
+    if overwrite || !path.is_file() {
+        if saved.is_file() {
+            std::fs::remove_file(&saved)?;
+        }
+        return Ok((Writer::from_path(path)?, 0));
+    }
+
+    let at = if saved.is_file() {
+        read_checkpoint(&saved)?
+    } else {
+        // killed before the first chunk was done, with at most the header
+        let content = std::fs::read(path)?;
+        if content.iter().filter(|c| **c == b'\n').count() > 1 {
+            bail!(
+                "{} has no checkpoint to continue a stream from (wasn't it streamed?), see --overwrite",
+                path.display()
+            );
+        }
+        Checkpoint::default()
+    };
+    let file = OpenOptions::new().append(true).open(path)?;
+    ensure!(
+        file.metadata()?.len() >= at.len,
+        "{} is shorter than at its checkpoint",
+        path.display()
+    );
+    file.set_len(at.len)?;
+    // serialized rows need a header if there isn't one yet
+    let writer = WriterBuilder::new()
+        .has_headers(at.len == 0)
+        .from_writer(file);
+    Ok((writer, at.rows))
+}
+
+/// note that the streamed output at `path` went through `rows` rows of the
+/// input, after its writer was flushed
+pub fn checkpoint(path: &Path, rows: usize) -> Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    // a checkpoint is replaced whole, so a killed run can't leave half of one
+    let saved = checkpoint_path(path);
+    let mut tmp = saved.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, format!("rows,len\n{rows},{len}\n"))?;
+    std::fs::rename(&tmp, &saved)?;
+    Ok(())
+}
+
+fn read_checkpoint(saved: &Path) -> Result<Checkpoint> {
+    let content = std::fs::read_to_string(saved)?;
+    let at = content.lines().nth(1).and_then(|line| {
+        let (rows, len) = line.split_once(',')?;
+        Some(Checkpoint {
+            rows: rows.parse().ok()?,
+            len: len.parse().ok()?,
+        })
+    });
+    at.ok_or_else(|| anyhow!("{} isn't rows,len", saved.display()))
+}
+
+/// where the checkpoint of the streamed output at `output` is kept
+pub fn checkpoint_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".checkpoint");
+    PathBuf::from(name)
+}
+
+/// `work` on every item of `source`, which is produced on another thread at
+/// most `ahead` items before `work` gets to it. stops at the first error of
+/// either.
+///
+/// ```
+/// # use lib::stream::pipeline;
+/// let mut sum = 0;
+/// pipeline((1..=100).map(Ok), 2, |x| {
+///     sum += x;
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert_eq!(sum, 5050);
+/// ```
+pub fn pipeline<T: Send>(
+    source: impl Iterator<Item = Result<T>> + Send,
+    ahead: usize,
+    mut work: impl FnMut(T) -> Result<()>,
+) -> Result<()> {
+    std::thread::scope(|s| {
+        let (tx, rx) = sync_channel(ahead);
+        s.spawn(move || {
+            for item in source {
+                let failed = item.is_err();
+                // no one's listening any more when work failed
+                if tx.send(item).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        for item in rx {
+            work(item?)?;
+        }
+        Ok(())
+    })
+}
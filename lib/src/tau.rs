@@ -8,8 +8,8 @@ use lib::def::Element;
 use lib::def::partial_from_string;
 use lib::def::strict_from_partial;
 use lib::tau_w::TauVariants;
-use lib::tau_w::tau_partial;
-use lib::tau_w::tau_w;
+use lib::tau_w::tau_auto;
+use lib::tau_w::tau_partial_auto;
 use lib::weights::ap_weight;
 use lib::weights::unweighted;
 
@@ -38,9 +38,9 @@ fn main() -> Result<()> {
     let tau = if let (Ok(strict_a), Ok(strict_b)) =
         (strict_from_partial(&rank_a), strict_from_partial(&rank_b))
     {
-        tau_w(&strict_a, &strict_b, weight)?
+        tau_auto(&strict_a, &strict_b, weight)?
     } else {
-        tau_partial(&rank_a, &rank_b, weight, variant)?
+        tau_partial_auto(&rank_a, &rank_b, weight, variant)?
     };
 
     println!("{tau}");
@@ -1,13 +1,20 @@
 use std::collections::BTreeMap;
 
 use anyhow::Result;
+use anyhow::anyhow;
 use itertools::Itertools;
 
 use crate::def::Element;
 use crate::def::PartialOrder;
 use crate::def::Ranking;
 use crate::def::StrictOrder;
+use crate::def::total_to_string;
+use crate::weights::Family;
+use crate::weights::WeightFn;
+use crate::weights::decompose;
+use crate::weights::is_unweighted;
 
+#[derive(Debug, Clone, Copy)]
 pub enum TauVariants {
     A,
     B,
@@ -23,8 +30,8 @@ pub enum TauVariants {
 /// # use lib::tau_w::tau_w;
 /// let A = vec![Some('a'), Some('b'), Some('c'), Some('d')];
 /// let B = vec![Some('a'), Some('b'), Some('c'), Some('d')];
-/// let w = |i,j| 1.0 / ((i + j + 1) as f64);
-/// assert_eq!(tau_w(&A, &B, w, lib::tau_w::TauVariants::A).unwrap(), 1.0);
+/// let w = |i: (usize, usize), j: (usize, usize)| 1.0 / ((i.0 + j.0 + 1) as f64);
+/// assert_eq!(tau_w(&A, &B, w).unwrap(), 1.0);
 /// ```
 pub fn tau_w<F: Fn((usize, usize), (usize, usize)) -> f64>(
     a: &StrictOrder,
@@ -62,6 +69,73 @@ pub fn tau_w<F: Fn((usize, usize), (usize, usize)) -> f64>(
     Ok(num / total_weight)
 }
 
+/// compute (unweighted) kendall's tau by counting discordant pairs with a merge
+/// sort, as in Knight (1966).
+///
+/// runs in O(n log n)
+pub fn tau_knight(a: &StrictOrder, b: &StrictOrder) -> Result<f64> {
+    let va = a.ensure_defined()?;
+    let vb = b.ensure_defined()?;
+    let (n, _item_set) = a.ensure_conjoint(b)?;
+
+    let pos_b: BTreeMap<Element, usize> = vb
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, e)| (*e, i))
+        .collect();
+    // the positions in b, listed in the order of a: every inversion is a
+    // discordant pair
+    let mut seq = va
+        .iter()
+        .flatten()
+        .map(|e| {
+            pos_b
+                .get(e)
+                .copied()
+                .ok_or_else(|| anyhow!("{e} is missing from {}", total_to_string(b)))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+
+    let discordant = count_inversions(&mut seq) as f64;
+    let total_pairs = (n * n.saturating_sub(1) / 2) as f64;
+
+    Ok((total_pairs - 2.0 * discordant) / total_pairs)
+}
+
+/// bottom-up merge sort of `v`, returning the number of inversions it had.
+pub fn count_inversions(v: &mut [usize]) -> usize {
+    let n = v.len();
+    let mut buf = v.to_vec();
+    let mut inversions = 0;
+    let mut width = 1;
+    while width < n {
+        for lo in (0..n).step_by(2 * width) {
+            let mid = (lo + width).min(n);
+            let hi = (lo + 2 * width).min(n);
+            let (mut i, mut j, mut k) = (lo, mid, lo);
+            while i < mid && j < hi {
+                if v[j] < v[i] {
+                    // v[j] jumps over everything left in the first run
+                    inversions += mid - i;
+                    buf[k] = v[j];
+                    j += 1;
+                } else {
+                    buf[k] = v[i];
+                    i += 1;
+                }
+                k += 1;
+            }
+            buf[k..k + mid - i].copy_from_slice(&v[i..mid]);
+            k += mid - i;
+            buf[k..k + hi - j].copy_from_slice(&v[j..hi]);
+        }
+        v.copy_from_slice(&buf);
+        width *= 2;
+    }
+    inversions
+}
+
 pub fn sign(a: usize, b: usize) -> f64 {
     if a > b {
         1.0
@@ -78,13 +152,13 @@ pub type RankIndexMap = BTreeMap<Element, (usize, usize)>;
 ///
 /// e.g.:
 /// ```
-/// # use lib::tau_h::index_map;
+/// # use lib::tau_w::index_map;
 /// let A = vec![vec!['a','b'], vec!['c']];
 /// let B = vec![vec!['b'], vec!['c', 'a']];
 /// let map = index_map(&A,&B);
-/// assert_eq!(map.get(&'a'), Some((0,1)).as_ref());
-/// assert_eq!(map.get(&'b'), Some((0,0)).as_ref());
-/// assert_eq!(map.get(&'c'), Some((2,1)).as_ref());
+/// assert_eq!(map.get(&'a'), Some((1,2)).as_ref());
+/// assert_eq!(map.get(&'b'), Some((1,1)).as_ref());
+/// assert_eq!(map.get(&'c'), Some((3,2)).as_ref());
 /// ```
 pub fn index_map(rank_a: &PartialOrder, rank_b: &PartialOrder) -> RankIndexMap {
     let mut map: BTreeMap<Element, (usize, usize)> = BTreeMap::new();
@@ -146,7 +220,25 @@ pub fn tau_partial<F: Fn((usize, usize), (usize, usize)) -> f64>(
         }
     }
 
-    let denom = match variant {
+    let denom = denominator(variant, total_weight, ties_a, ties_b, ties_both);
+
+    #[cfg(debug_assertions)]
+    {
+        println!("{concordance} / {denom}");
+    }
+    Ok(concordance / denom)
+}
+
+/// the denominator of each tau variant, given the total weight and the weight
+/// of pairs tied only in a, only in b, and in both.
+fn denominator(
+    variant: TauVariants,
+    total_weight: f64,
+    ties_a: f64,
+    ties_b: f64,
+    ties_both: f64,
+) -> f64 {
+    match variant {
         TauVariants::A => total_weight,
         TauVariants::B => {
             let sum_cd = total_weight - (ties_a + ties_b + ties_both); // = C+D
@@ -155,11 +247,202 @@ pub fn tau_partial<F: Fn((usize, usize), (usize, usize)) -> f64>(
             (denom_a * denom_b).sqrt()
         }
         TauVariants::W => unimplemented!(), // waiting on Gazeel (2025)
+    }
+}
+
+/// compute kendall's tau under weight function w, in O(n log n) if the weight
+/// allows it (see [`decompose`]), and with [`tau_w`] otherwise.
+///
+/// ```
+/// # use lib::tau_w::tau_auto;
+/// # use lib::tau_w::tau_w;
+/// # use lib::weights::hyperbolic_addtv_weight;
+/// let A = vec![Some('a'), Some('b'), Some('c'), Some('d'), Some('e')];
+/// let B = vec![Some('c'), Some('a'), Some('e'), Some('b'), Some('d')];
+/// let fast = tau_auto(&A, &B, hyperbolic_addtv_weight).unwrap();
+/// let slow = tau_w(&A, &B, hyperbolic_addtv_weight.as_fn()).unwrap();
+/// assert!((fast - slow).abs() < lib::PRECISION);
+/// ```
+pub fn tau_auto(a: &StrictOrder, b: &StrictOrder, w: WeightFn) -> Result<f64> {
+    if is_unweighted(w) {
+        return tau_knight(a, b);
+    }
+    let Some(terms) = decompose(w) else {
+        return tau_w(a, b, w.as_fn());
     };
 
-    #[cfg(debug_assertions)]
-    {
-        println!("{concordance} / {denom}");
+    let va = a.ensure_defined()?;
+    let vb = b.ensure_defined()?;
+    a.ensure_conjoint(b)?;
+
+    let pos_b: BTreeMap<Element, usize> = vb
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, e)| (*e, i))
+        .collect();
+    let positions = va
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, e)| {
+            pos_b
+                .get(e)
+                .map(|j| (i, *j))
+                .ok_or_else(|| anyhow!("{e} is missing from {}", total_to_string(b)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let sums = pair_sums(&positions, terms);
+    Ok((sums.total - 2.0 * sums.discordant) / sums.total)
+}
+
+/// same as [`tau_partial`], in O(n log n) if the weight allows it (see
+/// [`decompose`]).
+///
+/// ```
+/// # use lib::tau_w::TauVariants;
+/// # use lib::tau_w::tau_partial;
+/// # use lib::tau_w::tau_partial_auto;
+/// # use lib::weights::hyperbolic_sym_mult_weight;
+/// let A = vec![vec!['a', 'b'], vec!['c'], vec!['d', 'e', 'f']];
+/// let B = vec![vec!['f'], vec!['c', 'a', 'd'], vec!['b', 'e']];
+/// let w = hyperbolic_sym_mult_weight;
+/// let fast = tau_partial_auto(&A, &B, w, TauVariants::B).unwrap();
+/// let slow = tau_partial(&A, &B, w.as_fn(), TauVariants::B).unwrap();
+/// assert!((fast - slow).abs() < lib::PRECISION);
+/// ```
+pub fn tau_partial_auto(
+    a: &PartialOrder,
+    b: &PartialOrder,
+    w: WeightFn,
+    variant: TauVariants,
+) -> Result<f64> {
+    let Some(terms) = decompose(w) else {
+        return tau_partial(a, b, w.as_fn(), variant);
+    };
+
+    let positions = index_map(a, b).into_values().collect_vec();
+    let sums = pair_sums(&positions, terms);
+
+    let ties_a = sums.tied_a - sums.tied_both;
+    let ties_b = sums.tied_b - sums.tied_both;
+    let concordance = sums.total - sums.tied_a - ties_b - 2.0 * sums.discordant;
+
+    Ok(concordance / denominator(variant, sums.total, ties_a, ties_b, sums.tied_both))
+}
+
+/// weight sums over sets of pairs
+#[derive(Debug, Default)]
+struct PairSums {
+    /// all pairs
+    total: f64,
+    /// pairs tied in a (including those tied in both)
+    tied_a: f64,
+    /// pairs tied in b (including those tied in both)
+    tied_b: f64,
+    /// pairs tied in both
+    tied_both: f64,
+    /// pairs strictly ordered in opposite directions
+    discordant: f64,
+}
+
+/// sums of the weights of pairs, for a weight that is a sum of `coefficient *
+/// family` terms, given the (a, b) positions of every element.
+///
+/// runs in O(n log n): sort lexicographically by (a, b) and then merge sort by b
+/// while accumulating the weights of the pairs each element jumps over (Vigna
+/// 2015).
+fn pair_sums(positions: &[(usize, usize)], terms: &[(f64, Family)]) -> PairSums {
+    let mut by_a = positions.to_vec();
+    by_a.sort_unstable();
+    let mut by_b = positions.to_vec();
+    by_b.sort_unstable_by_key(|(pa, pb)| (*pb, *pa));
+
+    let mut sums = PairSums::default();
+    for (coef, family) in terms {
+        let (f, weight_of) = match family {
+            Family::Additive(f) => (f, additive_pairs as fn(&[f64]) -> f64),
+            Family::Multiplicative(f) => (f, multiplicative_pairs as fn(&[f64]) -> f64),
+        };
+        let runs = |sorted: &[(usize, usize)],
+                    same: fn(&(usize, usize), &(usize, usize)) -> bool| {
+            sorted
+                .chunk_by(same)
+                .map(|run| weight_of(&run.iter().map(|x| f(*x)).collect_vec()))
+                .sum::<f64>()
+        };
+
+        sums.total += coef * weight_of(&positions.iter().map(|x| f(*x)).collect_vec());
+        sums.tied_a += coef * runs(&by_a, |x, y| x.0 == y.0);
+        sums.tied_both += coef * runs(&by_a, |x, y| x == y);
+        sums.tied_b += coef * runs(&by_b, |x, y| x.1 == y.1);
+
+        let mut keys = by_a.iter().map(|x| x.1).collect_vec();
+        let mut values = by_a.iter().map(|x| f(*x)).collect_vec();
+        sums.discordant += coef * weighted_inversions(&mut keys, &mut values, family);
     }
-    Ok(concordance / denom)
+    sums
+}
+
+/// the weight of all pairs in a set of elements with additive values
+fn additive_pairs(values: &[f64]) -> f64 {
+    values.len().saturating_sub(1) as f64 * values.iter().sum::<f64>()
+}
+
+/// the weight of all pairs in a set of elements with multiplicative values
+fn multiplicative_pairs(values: &[f64]) -> f64 {
+    let sum = values.iter().sum::<f64>();
+    let sum_sq = values.iter().map(|v| v * v).sum::<f64>();
+    (sum * sum - sum_sq) / 2.0
+}
+
+/// like [`count_inversions`], but sums the weights of the inverted pairs, where
+/// each element has a value and the family says how to combine two of them.
+/// equal keys are not inversions.
+fn weighted_inversions(keys: &mut [usize], values: &mut [f64], family: &Family) -> f64 {
+    let n = keys.len();
+    let mut key_buf = keys.to_vec();
+    let mut value_buf = values.to_vec();
+    let mut suffix = vec![0.0; n + 1];
+    let mut inversions = 0.0;
+    let mut width = 1;
+    while width < n {
+        for lo in (0..n).step_by(2 * width) {
+            let mid = (lo + width).min(n);
+            let hi = (lo + 2 * width).min(n);
+            // suffix[i] is the sum of values[i..mid]
+            suffix[mid] = 0.0;
+            for i in (lo..mid).rev() {
+                suffix[i] = suffix[i + 1] + values[i];
+            }
+            let (mut i, mut j, mut k) = (lo, mid, lo);
+            while i < mid && j < hi {
+                if keys[j] < keys[i] {
+                    // keys[j] jumps over everything left in the first run
+                    inversions += match family {
+                        Family::Additive(_) => (mid - i) as f64 * values[j] + suffix[i],
+                        Family::Multiplicative(_) => values[j] * suffix[i],
+                    };
+                    key_buf[k] = keys[j];
+                    value_buf[k] = values[j];
+                    j += 1;
+                } else {
+                    key_buf[k] = keys[i];
+                    value_buf[k] = values[i];
+                    i += 1;
+                }
+                k += 1;
+            }
+            key_buf[k..k + mid - i].copy_from_slice(&keys[i..mid]);
+            value_buf[k..k + mid - i].copy_from_slice(&values[i..mid]);
+            k += mid - i;
+            key_buf[k..k + hi - j].copy_from_slice(&keys[j..hi]);
+            value_buf[k..k + hi - j].copy_from_slice(&values[j..hi]);
+        }
+        keys.copy_from_slice(&key_buf);
+        values.copy_from_slice(&value_buf);
+        width *= 2;
+    }
+    inversions
 }
@@ -1,125 +1,259 @@
 //! different weight functions. some of them inhibit the proposed algorithm's
 //! optimality.
+//!
+//! the weights are constants (see [`WeightFn`]), but they keep the lowercase
+//! names they have in [`NAMED`].
+#![allow(non_upper_case_globals)]
 
 use anyhow::Result;
+use anyhow::anyhow;
 
 use crate::def::StrictOrder;
-use crate::tau_w::tau_w;
+use crate::tau_w::tau_knight;
 
-/// no weight, kendall's tau
-pub fn unweighted(_: (usize, usize), _: (usize, usize)) -> f64 {
+/// a weight function, along with what we know about its shape, which is what
+/// decides the algorithms that can use it. it gets the (a, b) positions of the
+/// two elements of a pair.
+#[derive(Debug, Clone, Copy)]
+pub struct WeightFn {
+    f: fn((usize, usize), (usize, usize)) -> f64,
+    shape: Shape,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    Unweighted,
+    /// a (linear combination of) [`Family`] weights
+    Decomposable(&'static [(f64, Family)]),
+    Opaque,
+}
+
+impl WeightFn {
+    /// a weight we know nothing about, so only the quadratic algorithms use it
+    pub const fn new(f: fn((usize, usize), (usize, usize)) -> f64) -> WeightFn {
+        WeightFn {
+            f,
+            shape: Shape::Opaque,
+        }
+    }
+
+    const fn decomposable(
+        f: fn((usize, usize), (usize, usize)) -> f64,
+        terms: &'static [(f64, Family)],
+    ) -> WeightFn {
+        WeightFn {
+            f,
+            shape: Shape::Decomposable(terms),
+        }
+    }
+
+    /// the weight of the pair of elements at positions `x` and `y`
+    pub fn at(&self, x: (usize, usize), y: (usize, usize)) -> f64 {
+        (self.f)(x, y)
+    }
+
+    /// the plain function, for the algorithms that take any weight
+    pub fn as_fn(&self) -> fn((usize, usize), (usize, usize)) -> f64 {
+        self.f
+    }
+}
+
+/// is `w` the [`unweighted`] weight?
+/// some algorithms only work (or work a lot faster) without weights.
+pub fn is_unweighted(w: WeightFn) -> bool {
+    matches!(w.shape, Shape::Unweighted)
+}
+
+/// how the weight of a pair is built from a value per element, which is what
+/// allows tau to be computed in O(n log n) (Vigna 2015). each element's value
+/// only depends on its (a, b) positions, just like the weight functions.
+#[derive(Debug, Clone, Copy)]
+pub enum Family {
+    /// w(x, y) = f(x) + f(y)
+    Additive(fn((usize, usize)) -> f64),
+    /// w(x, y) = f(x) * f(y)
+    Multiplicative(fn((usize, usize)) -> f64),
+}
+
+/// the per-element decomposition of `w` as a sum of `coefficient * family`
+/// terms, if we know one. anything without one can only use the quadratic
+/// algorithms.
+///
+/// ```
+/// # use lib::weights::ap_weight;
+/// # use lib::weights::decompose;
+/// # use lib::weights::hyperbolic_sym_mult_weight;
+/// assert_eq!(decompose(hyperbolic_sym_mult_weight).unwrap().len(), 2);
+/// assert!(decompose(ap_weight).is_none());
+/// ```
+pub fn decompose(w: WeightFn) -> Option<&'static [(f64, Family)]> {
+    match w.shape {
+        Shape::Unweighted => Some(&[(1.0, Family::Multiplicative(one))]),
+        Shape::Decomposable(terms) => Some(terms),
+        Shape::Opaque => None,
+    }
+}
+
+/// every weight function, by the name of the function.
+pub const NAMED: &[(&str, WeightFn)] = &[
+    ("unweighted", unweighted),
+    ("hyperbolic_addtv_weight", hyperbolic_addtv_weight),
+    ("hyperbolic_mult_weight", hyperbolic_mult_weight),
+    ("hyperbolic_sym_mult_weight", hyperbolic_sym_mult_weight),
+    ("ap_weight", ap_weight),
+    ("ap_high_weight", ap_high_weight),
+    ("const_weight_42", const_weight_42),
+    ("weight_inv_left", weight_inv_left),
+    ("hyper_left_weight", hyper_left_weight),
+    ("weight_inv_right", weight_inv_right),
+    ("weight_right", weight_right),
+    ("weight_left", weight_left),
+    ("weight_zero", weight_zero),
+    ("weight_sum", weight_sum),
+    ("weight_inv_log", weight_inv_log),
+    ("threshold_bin_weight", threshold_bin_weight),
+    ("threshold_weight", threshold_weight),
+    ("rbo_weight", rbo_weight),
+    ("rbo_other_weight", rbo_other_weight),
+    ("expo_thresh_weight", expo_thresh_weight),
+];
+
+/// look up a weight function by its name (see [`NAMED`]).
+///
+/// ```
+/// # use lib::weights::by_name;
+/// # use lib::weights::is_unweighted;
+/// assert!(is_unweighted(by_name("unweighted").unwrap()));
+/// assert!(by_name("not_a_weight").is_err());
+/// ```
+pub fn by_name(name: &str) -> Result<WeightFn> {
+    NAMED
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, w)| *w)
+        .ok_or_else(|| anyhow!("unknown weight {name:?}"))
+}
+
+fn one(_: (usize, usize)) -> f64 {
     1.0
 }
 
+fn hyperbolic_a(x: (usize, usize)) -> f64 {
+    1.0 / (x.0 as f64 + 1.0)
+}
+
+fn hyperbolic_b(x: (usize, usize)) -> f64 {
+    1.0 / (x.1 as f64 + 1.0)
+}
+
+fn position_a(x: (usize, usize)) -> f64 {
+    x.0 as f64
+}
+
+/// no weight, kendall's tau
+pub const unweighted: WeightFn = WeightFn {
+    f: |_, _| 1.0,
+    shape: Shape::Unweighted,
+};
+
 /// an asymmetric additive hyperbolic weighting function, as described in Vigna
 /// 2014. it uses the left ranking X as the reference.
-pub fn hyperbolic_addtv_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
-    (1.0 / (x.0 as f64 + 1.0)) + (1.0 / (y.0 as f64 + 1.0))
-}
+pub const hyperbolic_addtv_weight: WeightFn = WeightFn::decomposable(
+    |x, y| (1.0 / (x.0 as f64 + 1.0)) + (1.0 / (y.0 as f64 + 1.0)),
+    &[(1.0, Family::Additive(hyperbolic_a))],
+);
 
 /// an asymmetric multiplicative hyperbolic weighting function, as described in
 /// Vigna 2014. it uses the left ranking X as the reference.
-pub fn hyperbolic_mult_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
-    (1.0 / (x.0 as f64 + 1.0)) * (1.0 / (y.0 as f64 + 1.0))
-}
+pub const hyperbolic_mult_weight: WeightFn = WeightFn::decomposable(
+    |x, y| (1.0 / (x.0 as f64 + 1.0)) * (1.0 / (y.0 as f64 + 1.0)),
+    &[(1.0, Family::Multiplicative(hyperbolic_a))],
+);
 
 /// a symmetric multiplicative hyperbolic weighting function, as described in
 /// Vigna 2014. it uses the left ranking X as the reference.
-pub fn hyperbolic_sym_mult_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
-    ((1.0 / (x.0 as f64 + 1.0)) * (1.0 / (y.0 as f64 + 1.0))
-        + (1.0 / (x.1 as f64 + 1.0)) * (1.0 / (y.1 as f64 + 1.0)))
-        / 2.0
-}
+pub const hyperbolic_sym_mult_weight: WeightFn = WeightFn::decomposable(
+    |x, y| {
+        ((1.0 / (x.0 as f64 + 1.0)) * (1.0 / (y.0 as f64 + 1.0))
+            + (1.0 / (x.1 as f64 + 1.0)) * (1.0 / (y.1 as f64 + 1.0)))
+            / 2.0
+    },
+    &[
+        (0.5, Family::Multiplicative(hyperbolic_a)),
+        (0.5, Family::Multiplicative(hyperbolic_b)),
+    ],
+);
 
 /// a weighting function to achieve tau_AP from Yilmaz 2008.
 /// assymetric, uses X as reference.
-pub fn ap_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
-    1.0 / (x.0.max(y.0) as f64)
-}
+pub const ap_weight: WeightFn = WeightFn::new(|x, y| 1.0 / (x.0.max(y.0) as f64));
 
 pub fn tau_unweighted(a: &StrictOrder, b: &StrictOrder) -> Result<f64> {
-    tau_w(a, b, unweighted)
+    tau_knight(a, b)
 }
 
 /// a weight I made up to test my hypothesis
-pub fn ap_high_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
-    1.0 / (x.0.min(y.0) as f64)
-}
+pub const ap_high_weight: WeightFn = WeightFn::new(|x, y| 1.0 / (x.0.min(y.0) as f64));
 
 /// a weight I made up to test my hypothesis
-pub fn const_weight_42(_x: (usize, usize), _y: (usize, usize)) -> f64 {
-    42.0
-}
+pub const const_weight_42: WeightFn = WeightFn::new(|_x, _y| 42.0);
 
 /// a weight I made up to test my hypothesis
-pub fn weight_inv_left(x: (usize, usize), _y: (usize, usize)) -> f64 {
-    1.0 / (x.0 as f64)
-}
+pub const weight_inv_left: WeightFn = WeightFn::new(|x, _y| 1.0 / (x.0 as f64));
 
 /// a weight I made up to test my hypothesis
-pub fn hyper_left_weight(x: (usize, usize), _y: (usize, usize)) -> f64 {
-    1.0 / (x.0 as f64 + 1.0)
-}
+pub const hyper_left_weight: WeightFn = WeightFn::new(|x, _y| 1.0 / (x.0 as f64 + 1.0));
 
 /// a weight I made up to test my hypothesis
-pub fn weight_inv_right(_x: (usize, usize), y: (usize, usize)) -> f64 {
-    1.0 / (y.0 as f64)
-}
+pub const weight_inv_right: WeightFn = WeightFn::new(|_x, y| 1.0 / (y.0 as f64));
 
 /// a weight I made up to test my hypothesis
-pub fn weight_right(_x: (usize, usize), y: (usize, usize)) -> f64 {
-    y.0 as f64
-}
+pub const weight_right: WeightFn = WeightFn::new(|_x, y| y.0 as f64);
 
 /// a weight I made up to test my hypothesis
-pub fn weight_left(x: (usize, usize), _y: (usize, usize)) -> f64 {
-    x.0 as f64
-}
+pub const weight_left: WeightFn = WeightFn::new(|x, _y| x.0 as f64);
 
 /// a weight I made up to test my hypothesis
-pub fn weight_zero(_x: (usize, usize), _y: (usize, usize)) -> f64 {
-    0.0
-}
+pub const weight_zero: WeightFn = WeightFn::new(|_x, _y| 0.0);
 
 /// a weight I made up to test my hypothesis
-pub fn weight_sum(x: (usize, usize), y: (usize, usize)) -> f64 {
-    (x.0 + y.0) as f64
-}
+pub const weight_sum: WeightFn = WeightFn::decomposable(
+    |x, y| (x.0 + y.0) as f64,
+    &[(1.0, Family::Additive(position_a))],
+);
 
 /// a weight I made up to test my hypothesis
-pub fn weight_inv_log(x: (usize, usize), y: (usize, usize)) -> f64 {
-    1.0 / ((x.0 + y.0 + 1) as f64).ln()
-}
+pub const weight_inv_log: WeightFn =
+    WeightFn::new(|x, y| 1.0 / ((x.0 + y.0 + 1) as f64).ln());
 
 /// a weight I made up to test my hypothesis
-pub fn threshold_bin_weight(x: (usize, usize), _y: (usize, usize)) -> f64 {
-    ((x.0 < 5) as usize) as f64
-}
+pub const threshold_bin_weight: WeightFn =
+    WeightFn::new(|x, _y| ((x.0 < 5) as usize) as f64);
 
 /// a weight I made up to test my hypothesis
-pub fn threshold_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
+pub const threshold_weight: WeightFn = WeightFn::new(|x, y| {
     let d = x.0.max(y.0);
     if d <= 5 {
         (2usize.pow(5 - d as u32)) as f64
     } else {
         0.0
     }
-}
+});
 
 /// a weight I made up to test my hypothesis
-pub fn rbo_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
+pub const rbo_weight: WeightFn = WeightFn::new(|x, y| {
     let p = 0.9f64;
     p.powi(x.0.max(y.0) as i32) / (1.0 - p)
-}
+});
 
 /// a weight I made up to test my hypothesis
-pub fn rbo_other_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
+pub const rbo_other_weight: WeightFn = WeightFn::new(|x, y| {
     let p = 0.5f64;
     p.powi(x.0.max(y.0) as i32)
-}
+});
 
 /// a weight I made up to test my hypothesis
-pub fn expo_thresh_weight(x: (usize, usize), y: (usize, usize)) -> f64 {
+pub const expo_thresh_weight: WeightFn = WeightFn::new(|x, y| {
     let d = x.0.max(y.0);
     (2usize.pow(5 - d as u32)) as f64
-}
+});
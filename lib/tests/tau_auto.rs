@@ -0,0 +1,67 @@
+//! the O(n log n) taus against the quadratic ones, for every weight and every
+//! (distinct) pair of rankings of up to 5 elements.
+use lib::PRECISION;
+use lib::def::partial_to_string;
+use lib::def::strict_from_partial;
+use lib::rwt::all_pairs;
+use lib::rwt::distinct_pairs;
+use lib::tau_w::TauVariants;
+use lib::tau_w::tau_auto;
+use lib::tau_w::tau_partial;
+use lib::tau_w::tau_partial_auto;
+use lib::tau_w::tau_w;
+use lib::weights::NAMED;
+
+/// are both the same number, or both not one?
+fn same(x: f64, y: f64) -> bool {
+    (x - y).abs() < PRECISION || (!x.is_finite() && !y.is_finite())
+}
+
+#[test]
+fn strict() {
+    for n in 2..=5 {
+        // a without ties and b any permutation, which is all of them up to
+        // renaming
+        let pairs = all_pairs(n)
+            .filter_map(|(a, b)| {
+                Some((strict_from_partial(&a).ok()?, strict_from_partial(&b).ok()?))
+            })
+            .collect::<Vec<_>>();
+        for (name, w) in NAMED {
+            for (a, b) in &pairs {
+                let fast = tau_auto(a, b, *w).unwrap();
+                let slow = tau_w(a, b, w.as_fn()).unwrap();
+                assert!(
+                    same(fast, slow),
+                    "{name} on {a:?} / {b:?}: {fast} fast, {slow} slow"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn partial() {
+    for n in 2..=5 {
+        let pairs = distinct_pairs(n).collect::<Vec<_>>();
+        for (name, w) in NAMED {
+            for (a, b) in &pairs {
+                // W isn't defined yet, and B is 0 / 0 (up to rounding, so
+                // either side can come out finite) when a ranking is one tie
+                for variant in [TauVariants::A, TauVariants::B] {
+                    if matches!(variant, TauVariants::B) && (a.len() == 1 || b.len() == 1) {
+                        continue;
+                    }
+                    let fast = tau_partial_auto(a, b, *w, variant).unwrap();
+                    let slow = tau_partial(a, b, w.as_fn(), variant).unwrap();
+                    assert!(
+                        same(fast, slow),
+                        "{name} on {} / {}: {fast} fast, {slow} slow",
+                        partial_to_string(a),
+                        partial_to_string(b)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod ref_solver;
+pub mod sim;
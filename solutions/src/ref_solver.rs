@@ -1,15 +1,66 @@
 use std::collections::BTreeMap;
+use std::time::Instant;
 
 use anyhow::Result;
 use anyhow::bail;
+use lib::AlgoOut;
 use lib::AlgoOutputRow;
 use lib::RankingsCsvRow;
+use lib::Usage;
+use lib::cache::Cache;
+use lib::cache::Cached;
 use lib::def::StrictOrder;
 use lib::def::partial_from_string;
 use lib::def::total_to_repl_string;
+use lib::display_cases;
 use solver::bounds::bf::tau_bounds_bf_unweighted;
 
-pub fn run_solver(inp: &RankingsCsvRow) -> Result<Option<AlgoOutputRow>> {
+/// the weight of the reference, as it's named in the cache
+pub const WEIGHT: &str = "unweighted";
+
+/// the version of the reference solutions, which names them in the cache. bump
+/// it when [`tau_bounds_bf_unweighted`], or what's kept of its output, changes.
+/// (2 counts the optimal arbitrations, and no longer skips the inputs with too
+/// many of them. 3 solves every block of tie groups on its own, so it skips
+/// fewer inputs.)
+pub const VERSION: u32 = 3;
+
+/// the name of the reference in the cache
+pub fn solver_name() -> String {
+    format!("bf-unweighted-v{VERSION}")
+}
+
+/// the reference solution of `inp`, or `None` if it's too big for the brute
+/// force. it lists at most [`solver::bounds::bf::MAX_SOLUTIONS`] optimal
+/// arbitrations, but counts all of them. with a cache, it's looked up there
+/// first and kept there after.
+pub fn run_solver(
+    inp: &RankingsCsvRow,
+    cache: Option<&Cache>,
+) -> Result<Option<AlgoOutputRow>> {
+    let solver = solver_name();
+    if let Some(cache) = cache
+        && let Some(hit) = cache.get(inp, WEIGHT, &solver)?
+    {
+        return Ok(hit.out.and_then(|out| to_row(inp, out)));
+    }
+
+    let start = Instant::now();
+    let out = solve(inp)?;
+    let time = start.elapsed();
+    // it runs in this process, so there's no usage of its own
+    let usage = Usage {
+        cpu: time,
+        max_rss: 0,
+    };
+    let result = Cached { out, time, usage };
+    if let Some(cache) = cache {
+        cache.put(inp, WEIGHT, &solver, &result)?;
+    }
+    Ok(result.out.and_then(|out| to_row(inp, out)))
+}
+
+fn solve(inp: &RankingsCsvRow) -> Result<Option<AlgoOut>> {
     let mut inp_map: BTreeMap<String, char> = BTreeMap::new();
     let rank_a = partial_from_string(&inp.a, &mut inp_map)?;
     let rank_b = partial_from_string(&inp.b, &mut inp_map)?;
@@ -22,28 +73,25 @@ pub fn run_solver(inp: &RankingsCsvRow) -> Result<Option<AlgoOutputRow>> {
                     .iter()
                     .map(|(x, y)| (*y, x.clone()))
                     .collect::<BTreeMap<char, String>>();
-                let join_sols = |aa: &Vec<StrictOrder>, bb: &Vec<StrictOrder>| {
+                let sols = |aa: &Vec<StrictOrder>, bb: &Vec<StrictOrder>| {
                     aa.iter()
                         .zip(bb.iter())
                         .map(|(a, b)| {
-                            format!(
-                                "{}/{}",
+                            (
                                 total_to_repl_string(a, &rmap),
-                                total_to_repl_string(b, &rmap)
+                                total_to_repl_string(b, &rmap),
                             )
                         })
-                        .collect::<Vec<String>>()
-                        .join("|")
+                        .collect::<Vec<(String, String)>>()
                 };
-                let min_sols = join_sols(&lb.a, &lb.b);
-                let max_sols = join_sols(&ub.a, &ub.b);
-                Ok(Some(AlgoOutputRow {
-                    a: inp.a.clone(),
-                    b: inp.b.clone(),
-                    tmin: lb.t,
-                    tmax: ub.t,
-                    pmin: min_sols,
-                    pmax: max_sols,
+                Ok(Some(AlgoOut {
+                    tmin: Some(lb.t),
+                    tmax: Some(ub.t),
+                    minp: sols(&lb.a, &lb.b),
+                    maxp: sols(&ub.a, &ub.b),
+                    nmin: lb.count,
+                    nmax: ub.count,
+                    ..Default::default()
                 }))
             } else {
                 bail!("reference solution did not return full solution (???)")
@@ -51,3 +99,16 @@ pub fn run_solver(inp: &RankingsCsvRow) -> Result<Option<AlgoOutputRow>> {
         }
     }
 }
+
+fn to_row(inp: &RankingsCsvRow, out: AlgoOut) -> Option<AlgoOutputRow> {
+    Some(AlgoOutputRow {
+        a: inp.a.clone(),
+        b: inp.b.clone(),
+        tmin: out.tmin?,
+        tmax: out.tmax?,
+        pmin: display_cases(&out.minp),
+        pmax: display_cases(&out.maxp),
+        nmin: out.nmin,
+        nmax: out.nmax,
+    })
+}
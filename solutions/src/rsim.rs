@@ -1,23 +1,31 @@
 //! Data generation (ranking simulation)
-#![allow(unused)]
+//!
+//! every row is a pair of rankings of between `lens_min` and `lens_max` items:
+//! a uniformly random one, and one from a mallows model around it whose tau is
+//! between a target in `taus_min..=taus_max` (see [`solutions::sim`]). then
+//! ties are put into each of them by a [`TieModel`] (`--ties-model`), which
+//! for most models ties between `ties_min` and `ties_max` items with the one
+//! before them. the model is written next to every row.
+//!
+//! every row has its own seed, which is written next to it: generating again
+//! with the same `--seed` gives the same rows, and the seed of a row is enough
+//! to make just that row again. a row whose mallows model doesn't get into the
+//! taus is skipped, with a warning.
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::time::Instant;
 
 use anyhow::Result;
-use anyhow::bail;
+use anyhow::ensure;
 use clap::Parser;
 use clap_derive::Parser;
-use csv::Reader;
-use csv::StringRecord;
 use csv::Writer;
-use indicatif::ParallelProgressIterator;
-use indicatif::ProgressBar;
-use indicatif::ProgressStyle;
-use lib::def::PartialOrder;
-use rayon::iter::IntoParallelRefIterator;
-use rayon::iter::ParallelIterator;
-
-const ABC: &str = "abcdefghijklmnopqrstuvwxyz";
+use lib::def::*;
+use lib::rng::Rng;
+use solutions::sim::TieModel;
+use solutions::sim::calibrate;
+use solutions::sim::induce_ties;
+use solutions::sim::mallows_pair;
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -26,20 +34,101 @@ pub struct Cli {
     pub lens_max: usize,
     pub ties_min: usize,
     pub ties_max: usize,
-    pub taus_min: f32,
-    pub taus_max: f32,
+    pub taus_min: f64,
+    pub taus_max: f64,
+    pub output: PathBuf,
+    /// how many pairs to generate
+    #[arg(long, default_value_t = 1000)]
+    pub count: usize,
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// how ties are put in: neighbours, top, tail, runs:len, rounding:decimals
+    /// or grades:count
+    #[arg(long, default_value = "neighbours")]
+    pub ties_model: TieModel,
+}
+
+#[derive(Debug, Clone, serde_derive::Serialize)]
+struct SimRow {
+    a: String,
+    b: String,
+    seed: u64,
+    ties: String,
 }
 
 fn main() -> Result<()> {
+    let args = Cli::parse();
+    ensure!(
+        2 <= args.lens_min && args.lens_min <= args.lens_max,
+        "lengths must be at least 2, and min <= max"
+    );
+    ensure!(args.ties_min <= args.ties_max, "ties: min must be <= max");
+    ensure!(
+        -1.0 <= args.taus_min && args.taus_min <= args.taus_max && args.taus_max <= 1.0,
+        "taus must be in [-1, 1], and min <= max"
+    );
+
+    let start = Instant::now();
+    let mut writer = Writer::from_path(&args.output)?;
+    let mut seeds = Rng::new(args.seed);
+    // the dispersion only depends on the length, and takes a while to find
+    let mut thetas = BTreeMap::new();
+    let mut skipped = 0;
+    for _ in 0..args.count {
+        let seed = seeds.next_u64();
+        let Some((a, b)) = generate(&args, seed, &mut thetas)? else {
+            skipped += 1;
+            continue;
+        };
+        writer.serialize(SimRow {
+            a,
+            b,
+            seed,
+            ties: args.ties_model.to_string(),
+        })?;
+    }
+    writer.flush()?;
+
+    println!(
+        "generated {} test cases ({skipped} skipped) in {}s",
+        args.count - skipped,
+        start.elapsed().as_secs_f32()
+    );
     Ok(())
 }
 
-/// # integer representation of a ranking with ties
-/// - one u64 has 16 * 4 bits with indices 0-15
-/// - the index of the 4 bit number within the u64 corresponds to an index in
-///   &ABC, which gives us the name (char) of an element
-/// - the value of the 4 bit number is the rank of the element from the previous
-///   step
-pub fn int_to_rwt(i: u64) -> PartialOrder {
-    todo!()
+/// the row with this seed, if the taus can be hit at its length. `thetas` are
+/// the dispersions of the lengths so far.
+fn generate(
+    args: &Cli,
+    seed: u64,
+    thetas: &mut BTreeMap<usize, f64>,
+) -> Result<Option<(String, String)>> {
+    let mut rng = Rng::new(seed);
+    let n = args.lens_min + rng.below(args.lens_max - args.lens_min + 1);
+    let taus = (args.taus_min, args.taus_max);
+    let theta = *thetas
+        .entry(n)
+        .or_insert_with(|| calibrate(n, (taus.0 + taus.1) / 2.0));
+    let (a, b) = match mallows_pair(&mut rng, n, taus, theta) {
+        Ok(pair) => pair,
+        Err(e) => {
+            eprintln!("warning: skipping the row with seed {seed}: {e}");
+            return Ok(None);
+        }
+    };
+
+    let ties = |rng: &mut Rng| args.ties_min + rng.below(args.ties_max - args.ties_min + 1);
+    let (ta, tb) = (ties(&mut rng), ties(&mut rng));
+    let a = induce_ties(&mut rng, &a, args.ties_model, ta);
+    let b = induce_ties(&mut rng, &b, args.ties_model, tb);
+
+    // numbers, since there can be more items than letters
+    let names = (0..n)
+        .map(|i| (element_from_index(i), (i + 1).to_string()))
+        .collect::<BTreeMap<Element, String>>();
+    Ok(Some((
+        partial_to_repl_string(&a, &names),
+        partial_to_repl_string(&b, &names),
+    )))
 }
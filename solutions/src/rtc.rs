@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -5,22 +6,45 @@ use anyhow::Result;
 use anyhow::anyhow;
 use clap::Parser;
 use clap_derive::Parser;
-use csv::Writer;
 use indicatif::ParallelProgressIterator;
 use lib::CHUNK_SIZE;
 use lib::RankingsCsvRow;
+use lib::cache::Cache;
 use lib::parse_row;
 use lib::progress_bar;
 use lib::read_glob_csv;
+use lib::resume_csv;
+use lib::shard::Shard;
+use lib::shard::output_path;
+use lib::stream::checkpoint;
+use lib::stream::count_rows;
+use lib::stream::pipeline;
+use lib::stream::resume_stream;
+use lib::stream::stream_rows;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use solutions::ref_solver::run_solver;
 
+const HEADER: &[&str] = &["a", "b"];
+
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
     pub input: String,
     pub output: PathBuf,
+    /// only run slice i of n (from 1) of the cases, see `lib::shard`
+    #[arg(long)]
+    pub shard: Option<Shard>,
+    /// read the input a chunk at a time, in its order, instead of all at once
+    /// (see `lib::stream`). can't be sharded
+    #[arg(long, conflicts_with = "shard")]
+    pub stream: bool,
+    /// start over instead of continuing the rows already in `output`
+    #[arg(long)]
+    pub overwrite: bool,
+    /// keep reference solutions in (and take them from) this directory
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -28,26 +52,27 @@ fn main() -> Result<()> {
 
     let start = Instant::now();
 
-    let rows = read_glob_csv(&args.input, vec!["a", "b"])?;
-
-    let num_cases = rows.len();
+    // carry on where an earlier run stopped
+    if let Some(shard) = args.shard {
+        shard.start(&args.output)?;
+    }
+    let output = output_path(&args.output, args.shard);
+    let (mut writer, done, skip) = if args.stream {
+        let (writer, skip) = resume_stream(&output, args.overwrite)?;
+        (writer, HashSet::new(), skip)
+    } else {
+        let (writer, done) = resume_csv(&output, args.overwrite)?;
+        (writer, done, 0)
+    };
 
-    let mut cases = rows
-        .par_iter()
-        .map(parse_row)
-        .collect::<Result<Vec<RankingsCsvRow>>>()?;
-    cases.dedup();
-    cases.sort_by_key(|row| row.a.len());
+    let cache = args.cache.as_deref().map(Cache::open).transpose()?;
 
-    let pb = progress_bar(num_cases as u64)?;
+    let pb = progress_bar(0)?;
     let mut count = 0;
-
-    let mut writer = Writer::from_path(&args.output)?;
-
-    cases.chunks(CHUNK_SIZE).try_for_each(|group| {
+    let mut solve = |group: &[RankingsCsvRow]| {
         let outputs = group
             .par_iter()
-            .map(run_solver)
+            .map(|c| run_solver(c, cache.as_ref()))
             .progress_with(pb.clone())
             .collect::<Result<Vec<_>>>()
             .map_err(|e| anyhow!("runner err: {e:?}"))?
@@ -61,9 +86,54 @@ fn main() -> Result<()> {
                 count += 1;
                 writer.serialize(o)
             })
-            .map_err(|e| anyhow!("writer err: {e:?}"))
-    })?;
+            .map_err(|e| anyhow!("writer err: {e:?}"))?;
+        // so a killed run loses at most this group
+        writer.flush()?;
+        Ok::<(), anyhow::Error>(())
+    };
+
+    let num_cases = if args.stream {
+        let total = count_rows(&args.input, HEADER)?;
+        if skip > 0 {
+            println!("{skip} already done, {} to go", total - skip);
+        }
+        pb.set_length((total - skip) as u64);
+        let chunks = stream_rows(&args.input, HEADER, skip..total, CHUNK_SIZE)?;
+        let mut rows = skip;
+        pipeline(chunks, 2, |mut group| {
+            rows += group.len();
+            group.dedup();
+            solve(&group)?;
+            checkpoint(&output, rows)
+        })?;
+        total
+    } else {
+        let rows = read_glob_csv(&args.input, HEADER.to_vec())?;
+
+        let mut cases = rows
+            .par_iter()
+            .map(parse_row)
+            .collect::<Result<Vec<RankingsCsvRow>>>()?;
+        cases.dedup();
+        cases.sort_by_key(|row| row.a.len());
+        if let Some(shard) = args.shard {
+            shard.select(&mut cases);
+        }
+        let num_cases = cases.len();
+
+        cases.retain(|c| !done.contains(c));
+        if !done.is_empty() {
+            println!("{} already done, {} to go", done.len(), cases.len());
+        }
+        pb.set_length(cases.len() as u64);
+        cases.chunks(CHUNK_SIZE).try_for_each(&mut solve)?;
+        num_cases
+    };
 
+    if let Some(shard) = args.shard {
+        // the reference has no limits
+        shard.finish(&args.output, num_cases, 0)?;
+    }
     println!(
         "evaluated {count} test cases in {}s",
         start.elapsed().as_secs_f32()
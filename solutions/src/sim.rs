@@ -0,0 +1,338 @@
+//! synthetic pairs of rankings
+//!
+//! the second ranking is sampled from a mallows model around the first: the
+//! chance of a permutation falls off as `exp(-theta * d)`, with `d` the number
+//! of pairs it puts the other way around (the kendall distance). `theta` is the
+//! dispersion, which [`calibrate`] picks so that the expected tau is a target.
+//!
+//! sampling uses the repeated insertion model: item `i` goes `v` places before
+//! the end of the first `i` items with chance proportional to `exp(-theta * v)`,
+//! which adds exactly `v` discordant pairs.
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use lib::def::*;
+use lib::rng::Rng;
+
+/// how many times to sample before giving up on hitting a tau range
+const TRIES: usize = 1000;
+
+/// the expected tau of a permutation of `n` items from the mallows model with
+/// dispersion `theta`, compared to its centre.
+///
+/// ```
+/// # use solutions::sim::expected_tau;
+/// assert!(expected_tau(10, 0.0).abs() < 1e-9);
+/// assert!(expected_tau(10, 50.0) > 0.999);
+/// assert!((expected_tau(10, -1.0) + expected_tau(10, 1.0)).abs() < 1e-9);
+/// ```
+pub fn expected_tau(n: usize, theta: f64) -> f64 {
+    if n < 2 {
+        return 1.0;
+    }
+    let pairs = (n * (n - 1) / 2) as f64;
+    let distance = (0..n).map(|i| expected_shift(i, theta)).sum::<f64>();
+    1.0 - 2.0 * distance / pairs
+}
+
+/// the dispersion for which the expected tau of `n` items is `tau`
+///
+/// ```
+/// # use solutions::sim::calibrate;
+/// # use solutions::sim::expected_tau;
+/// let theta = calibrate(20, 0.6);
+/// assert!((expected_tau(20, theta) - 0.6).abs() < 1e-6);
+/// ```
+pub fn calibrate(n: usize, tau: f64) -> f64 {
+    // the expected tau only goes up with theta
+    let (mut lo, mut hi) = (-50.0, 50.0);
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if expected_tau(n, mid) < tau {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// a permutation of `0..n` from the mallows model with dispersion `theta`
+/// around the identity.
+pub fn mallows(rng: &mut Rng, n: usize, theta: f64) -> Vec<usize> {
+    let mut perm = Vec::with_capacity(n);
+    for i in 0..n {
+        let shift = sample_shift(rng, i, theta);
+        perm.insert(i - shift, i);
+    }
+    perm
+}
+
+/// a strict pair of `n` items: a uniformly random one, and one from the mallows
+/// model with dispersion `theta` around it with its tau in `taus`. the model is
+/// sampled until one is in it, so `theta` should be [`calibrate`]d to the
+/// middle of the range.
+///
+/// ```
+/// # use lib::rng::Rng;
+/// # use lib::tau_w::tau_knight;
+/// # use solutions::sim::calibrate;
+/// # use solutions::sim::mallows_pair;
+/// let theta = calibrate(30, 0.45);
+/// let (a, b) = mallows_pair(&mut Rng::new(3), 30, (0.4, 0.5), theta).unwrap();
+/// let t = tau_knight(&a, &b).unwrap();
+/// assert!((0.4..=0.5).contains(&t));
+/// ```
+pub fn mallows_pair(
+    rng: &mut Rng,
+    n: usize,
+    taus: (f64, f64),
+    theta: f64,
+) -> Result<(StrictOrder, StrictOrder)> {
+    let mut a = (0..n).map(element_from_index).collect::<Vec<Element>>();
+    for i in (1..n).rev() {
+        a.swap(i, rng.below(i + 1));
+    }
+
+    for _ in 0..TRIES {
+        let perm = mallows(rng, n, theta);
+        // a sample with d discordant pairs has tau 1 - 2d/pairs
+        let discordant = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .filter(|(i, j)| perm[*i] > perm[*j])
+            .count();
+        let tau =
+            1.0 - 2.0 * discordant as f64 / (n * n.saturating_sub(1) / 2).max(1) as f64;
+        if taus.0 <= tau && tau <= taus.1 {
+            let b = perm.iter().map(|i| Some(a[*i])).collect();
+            return Ok((a.into_iter().map(Some).collect(), b));
+        }
+    }
+    bail!(
+        "no ranking of {n} items with tau in [{}, {}] after {TRIES} tries",
+        taus.0,
+        taus.1
+    );
+}
+
+/// how ties are put into a strict ranking. the models that take a number of
+/// ties tie that many items with the one before them; the others decide on
+/// their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieModel {
+    /// items picked uniformly (`neighbours`)
+    Neighbours,
+    /// runs of about `len` neighbouring items at a time (`runs:len`)
+    Runs(usize),
+    /// items picked more often near the top (`top`)
+    TopHeavy,
+    /// items picked more often near the bottom (`tail`)
+    TailHeavy,
+    /// latent standard normal scores, sorted to fit the ranking and rounded to
+    /// this many decimals: items whose scores round the same are tied
+    /// (`rounding:d`)
+    Rounding(u32),
+    /// the same scores, put into this many equally wide grades (`grades:g`)
+    Grades(usize),
+}
+
+/// put ties into `order` following `model`, which might take `ties` into
+/// account (see [`TieModel`]).
+///
+/// ```
+/// # use lib::rng::Rng;
+/// # use lib::def::Ranking;
+/// # use solutions::sim::TieModel;
+/// # use solutions::sim::induce_ties;
+/// let order = "abcdefghij".chars().map(Some).collect::<Vec<_>>();
+/// let ties = |model: &str, seed| induce_ties(&mut Rng::new(seed), &order, model.parse().unwrap(), 3);
+///
+/// // the models that take a number of ties make exactly that many
+/// for model in ["neighbours", "runs:2", "top", "tail"] {
+///     let r = ties(model, 1);
+///     assert_eq!((r.set_size(), r.len()), (10, 7), "{model}");
+/// }
+/// // and top puts them higher up than tail: the places of the tied items,
+/// // summed over some seeds
+/// let places = |model| {
+///     (0..100)
+///         .flat_map(|seed| {
+///             let r = ties(model, seed);
+///             let starts = r.iter().scan(0, |at, tg| Some(std::mem::replace(at, *at + tg.len())));
+///             starts.zip(r.iter()).flat_map(|(at, tg)| at + 1..at + tg.len()).collect::<Vec<_>>()
+///         })
+///         .sum::<usize>()
+/// };
+/// assert!(places("top") < places("tail"));
+///
+/// // standard normal scores rounded to whole numbers are in a few groups, and
+/// // to many decimals in none
+/// assert!(ties("rounding:0", 1).len() <= 7);
+/// assert_eq!(ties("rounding:9", 1).len(), 10);
+/// assert!(ties("grades:3", 1).len() <= 3);
+/// assert_eq!(ties("grades:1", 1).len(), 1);
+/// assert!("grades:0".parse::<TieModel>().is_err());
+/// ```
+pub fn induce_ties(
+    rng: &mut Rng,
+    order: &StrictOrder,
+    model: TieModel,
+    ties: usize,
+) -> PartialOrder {
+    let n = order.len();
+    let ties = ties.min(n.saturating_sub(1));
+    // which of the items 1..n join the tie group before them
+    let mut joins = vec![false; n];
+    match model {
+        TieModel::Neighbours => pick_joins(rng, &mut joins, ties, |_| 1.0),
+        TieModel::TopHeavy => pick_joins(rng, &mut joins, ties, |k| (n - k) as f64),
+        TieModel::TailHeavy => pick_joins(rng, &mut joins, ties, |k| k as f64),
+        TieModel::Runs(len) => {
+            let mut tied = 0;
+            while tied < ties {
+                let start = 1 + rng.below(n - 1);
+                let run = 1 + rng.below(2 * len.max(1) - 1);
+                for join in joins.iter_mut().skip(start).take(run) {
+                    if !*join && tied < ties {
+                        *join = true;
+                        tied += 1;
+                    }
+                }
+            }
+        }
+        TieModel::Rounding(decimals) => {
+            let scale = 10f64.powi(decimals as i32);
+            let scores = latent_scores(rng, n);
+            for k in 1..n {
+                joins[k] = (scores[k] * scale).round() == (scores[k - 1] * scale).round();
+            }
+        }
+        TieModel::Grades(grades) => {
+            let scores = latent_scores(rng, n);
+            let (hi, lo) = (scores[0], scores[n - 1]);
+            let grade = |z: f64| {
+                let g = ((hi - z) / (hi - lo) * grades as f64) as usize;
+                g.min(grades.saturating_sub(1))
+            };
+            for k in 1..n {
+                joins[k] = grade(scores[k]) == grade(scores[k - 1]);
+            }
+        }
+    }
+
+    let mut rank: PartialOrder = Vec::new();
+    for (e, join) in order.iter().flatten().zip(joins) {
+        match rank.last_mut() {
+            Some(tg) if join => tg.push(*e),
+            _ => rank.push(vec![*e]),
+        }
+    }
+    rank
+}
+
+/// set `ties` of `joins[1..]`, picking `k` with weight `weight(k)`
+fn pick_joins(
+    rng: &mut Rng,
+    joins: &mut [bool],
+    ties: usize,
+    weight: impl Fn(usize) -> f64,
+) {
+    let mut candidates = (1..joins.len()).collect::<Vec<usize>>();
+    for _ in 0..ties {
+        let total = candidates.iter().map(|k| weight(*k)).sum::<f64>();
+        let mut u = rng.uniform() * total;
+        let mut i = 0;
+        while i + 1 < candidates.len() && u >= weight(candidates[i]) {
+            u -= weight(candidates[i]);
+            i += 1;
+        }
+        joins[candidates.swap_remove(i)] = true;
+    }
+}
+
+/// `n` standard normal scores (box-muller), from high to low
+fn latent_scores(rng: &mut Rng, n: usize) -> Vec<f64> {
+    let mut scores = (0..n)
+        .map(|_| {
+            let (u, v) = (1.0 - rng.uniform(), rng.uniform());
+            (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+        })
+        .collect::<Vec<f64>>();
+    scores.sort_by(|x, y| y.total_cmp(x));
+    scores
+}
+
+/// the expected number of places item `i` is inserted before the end
+fn expected_shift(i: usize, theta: f64) -> f64 {
+    if theta < 0.0 {
+        // shifting by v with -theta is shifting by i - v with theta
+        return i as f64 - expected_shift(i, -theta);
+    }
+    let q = (-theta).exp();
+    let (mut num, mut den, mut qv) = (0.0, 0.0, 1.0);
+    for v in 0..=i {
+        num += v as f64 * qv;
+        den += qv;
+        qv *= q;
+    }
+    num / den
+}
+
+fn sample_shift(rng: &mut Rng, i: usize, theta: f64) -> usize {
+    if theta < 0.0 {
+        return i - sample_shift(rng, i, -theta);
+    }
+    let q = (-theta).exp();
+    let total = (0..=i).map(|v| q.powi(v as i32)).sum::<f64>();
+    let mut u = rng.uniform() * total;
+    let mut qv = 1.0;
+    for v in 0..i {
+        if u < qv {
+            return v;
+        }
+        u -= qv;
+        qv *= q;
+    }
+    i
+}
+
+impl FromStr for TieModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, param) = s.split_once(':').unwrap_or((s, ""));
+        let param = || -> Result<usize> {
+            param
+                .parse::<usize>()
+                .map_err(|e| anyhow!("{name} needs a whole number, like {name}:2 ({e})"))
+        };
+        Ok(match name {
+            "neighbours" => TieModel::Neighbours,
+            "top" => TieModel::TopHeavy,
+            "tail" => TieModel::TailHeavy,
+            "runs" => TieModel::Runs(param()?),
+            "rounding" => TieModel::Rounding(param()? as u32),
+            "grades" => match param()? {
+                0 => bail!("grades needs at least one grade"),
+                g => TieModel::Grades(g),
+            },
+            _ => bail!("unknown tie model {s:?}"),
+        })
+    }
+}
+
+impl Display for TieModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TieModel::Neighbours => write!(f, "neighbours"),
+            TieModel::TopHeavy => write!(f, "top"),
+            TieModel::TailHeavy => write!(f, "tail"),
+            TieModel::Runs(len) => write!(f, "runs:{len}"),
+            TieModel::Rounding(d) => write!(f, "rounding:{d}"),
+            TieModel::Grades(g) => write!(f, "grades:{g}"),
+        }
+    }
+}
@@ -1,11 +1,25 @@
 //! brute force calculation of $\tau_{min}, \tau_{max}$
 use anyhow::Result;
 use cli::compute;
-use lib::tau_w::tau_w;
+use lib::tau_w::tau_auto;
 use solver::bounds::bf::tau_bounds_bf;
+use solver::bounds::blocks::by_blocks;
+use solver::bounds::certificate::certify_bounds;
 
 mod cli;
 
 fn main() -> Result<()> {
-    compute(|a, b, w| tau_bounds_bf(a, b, |x, y| tau_w(x, y, w)))
+    compute(|a, b, w, extras| {
+        let mut bounds = by_blocks(a, b, w, |x, y, w| {
+            tau_bounds_bf(x, y, |p, q| tau_auto(p, q, w))
+        })?;
+        if let Some(limit) = extras.all {
+            bounds.lb.iter_mut().for_each(|lb| lb.truncate(limit));
+            bounds.ub.iter_mut().for_each(|ub| ub.truncate(limit));
+        }
+        if extras.certify {
+            certify_bounds(a, b, &mut bounds, w)?;
+        }
+        Ok(bounds)
+    })
 }
@@ -5,6 +5,9 @@ use anyhow::bail;
 use itertools::Itertools;
 use lib::def::*;
 use lib::weights::tau_unweighted;
+use lib::weights::unweighted;
+
+use crate::bounds::blocks::by_blocks;
 
 impl BruteForce for PartialOrder {
     fn completions(&self) -> Vec<StrictOrder> {
@@ -31,8 +34,16 @@ impl BruteForce for PartialOrder {
     }
 }
 
+/// the most optimal solutions (of both bounds together) we are willing to list.
+/// the ones after that are only counted.
+pub const MAX_SOLUTIONS: usize = 8000;
+
+/// the brute force without weights, one block of tie groups at a time
+/// (see [`by_blocks`])
 pub fn tau_bounds_bf_unweighted(a: &PartialOrder, b: &PartialOrder) -> Result<TauBounds> {
-    tau_bounds_bf(a, b, tau_unweighted)
+    by_blocks(a, b, unweighted, |x, y, _| {
+        tau_bounds_bf(x, y, tau_unweighted)
+    })
 }
 
 pub fn tau_bounds_bf<F: Fn(&StrictOrder, &StrictOrder) -> Result<f64>>(
@@ -54,26 +65,33 @@ pub fn tau_bounds_bf<F: Fn(&StrictOrder, &StrictOrder) -> Result<f64>>(
     let mut ub = f64::NEG_INFINITY;
     let mut min_pairs = Vec::new();
     let mut max_pairs = Vec::new();
+    let (mut min_count, mut max_count) = (0u128, 0u128);
 
     for x in &le_a {
         for y in &le_b {
-            if min_pairs.len() + max_pairs.len() >= 8000 {
-                bail!("skipped: too many solutions")
-            }
+            let full = min_pairs.len() + max_pairs.len() >= MAX_SOLUTIONS;
             let t = tau(x, y)?;
             if t < lb {
                 lb = t;
                 min_pairs.clear();
                 min_pairs.push((x.clone(), y.clone()));
+                min_count = 1;
             } else if t == lb {
-                min_pairs.push((x.clone(), y.clone()));
+                if !full {
+                    min_pairs.push((x.clone(), y.clone()));
+                }
+                min_count += 1;
             }
             if t > ub {
                 ub = t;
                 max_pairs.clear();
                 max_pairs.push((x.clone(), y.clone()));
+                max_count = 1;
             } else if t == ub {
-                max_pairs.push((x.clone(), y.clone()));
+                if !full {
+                    max_pairs.push((x.clone(), y.clone()));
+                }
+                max_count += 1;
             }
         }
     }
@@ -81,11 +99,15 @@ pub fn tau_bounds_bf<F: Fn(&StrictOrder, &StrictOrder) -> Result<f64>>(
     Ok(TauBounds {
         lb: Some(Bound {
             t: lb,
+            count: Some(min_count),
+            certificate: None,
             a: min_pairs.iter().map(|(a, _b)| a.clone()).collect(),
             b: min_pairs.into_iter().map(|(_a, b)| b).collect(),
         }),
         ub: Some(Bound {
             t: ub,
+            count: Some(max_count),
+            certificate: None,
             a: max_pairs.iter().map(|(a, _b)| a.clone()).collect(),
             b: max_pairs.into_iter().map(|(_a, b)| b).collect(),
         }),
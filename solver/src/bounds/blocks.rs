@@ -0,0 +1,248 @@
+//! splitting an input into independent blocks
+//!
+//! whether a pair ends up concordant can only depend on the arbitration if the
+//! pair is tied in at least one of the rankings. so if we connect every tie
+//! group of a with the tie groups of b it shares items with, the arbitrations
+//! of different connected components (blocks) don't interact: without weights,
+//! every block can be solved on its own and the solutions put back together.
+//!
+//! this matters mostly for the brute force, which then only has to go over the
+//! linear extensions of one block at a time (a sum instead of a product).
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::ensure;
+use itertools::Itertools;
+use lib::def::*;
+use lib::tau_w::tau_auto;
+use lib::weights::WeightFn;
+use lib::weights::is_unweighted;
+
+use crate::bounds::bf::MAX_SOLUTIONS;
+use crate::bounds::tie_group_index;
+
+/// the part of the input that one connected component of tie groups covers.
+/// both rankings keep the relative order of the original.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub a: PartialOrder,
+    pub b: PartialOrder,
+}
+
+/// the blocks of the input that contain ties. items that are in none of them
+/// are not tied in either ranking, so they are already fixed.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use solver::bounds::blocks::blocks;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2) 3 (4 5) 6", &mut map).unwrap();
+/// let b = partial_from_string("2 1 (4 6) 5 3", &mut map).unwrap();
+/// let blocks = blocks(&a, &b);
+/// assert_eq!(blocks.len(), 2);
+/// assert_eq!(blocks[0].a, vec![vec!['a', 'b']]);
+/// assert_eq!(blocks[1].b, vec![vec!['d', 'f'], vec!['e']]);
+/// ```
+pub fn blocks(rank_a: &PartialOrder, rank_b: &PartialOrder) -> Vec<Block> {
+    let groups_a = tie_group_index(rank_a);
+    let groups_b = tie_group_index(rank_b);
+
+    // tie groups of a are nodes 0..rank_a.len(), those of b come after them
+    let offset = rank_a.len();
+    let mut parent = (0..offset + rank_b.len()).collect::<Vec<usize>>();
+    for (e, ga) in &groups_a {
+        let ra = find(&mut parent, *ga);
+        let rb = find(&mut parent, offset + groups_b[e]);
+        parent[ra] = rb;
+    }
+
+    // collect the tie groups of every component, in their original order
+    let mut by_root: BTreeMap<usize, Block> = BTreeMap::new();
+    for (i, tg) in rank_a.iter().enumerate() {
+        let root = find(&mut parent, i);
+        by_root
+            .entry(root)
+            .or_insert_with(Block::empty)
+            .a
+            .push(tg.clone());
+    }
+    for (i, tg) in rank_b.iter().enumerate() {
+        let root = find(&mut parent, offset + i);
+        by_root
+            .entry(root)
+            .or_insert_with(Block::empty)
+            .b
+            .push(tg.clone());
+    }
+
+    // a component of one item is a single fixed element in both rankings
+    let mut blocks = by_root
+        .into_values()
+        .filter(|block| block.a.set_size() > 1)
+        .collect::<Vec<Block>>();
+    // in the order they appear in a
+    blocks.sort_by_key(|block| groups_a[&block.a[0][0]]);
+    blocks
+}
+
+/// solve every block of the input with `algo`, and put the solutions of the
+/// blocks back together into the bounds of the whole input.
+///
+/// this is only exact without weights: with weights, the weight of a pair
+/// depends on the positions of its items, which depend on all the blocks. in
+/// that case, or if there is nothing to split, `algo` gets the whole input.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::tau_w::tau_auto;
+/// # use lib::weights::unweighted;
+/// # use solver::bounds::bf::tau_bounds_bf;
+/// # use solver::bounds::blocks::by_blocks;
+/// let bf = |a: &_, b: &_, w| tau_bounds_bf(a, b, |x, y| tau_auto(x, y, w));
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6 7) 8", &mut map).unwrap();
+/// let b = partial_from_string("3 (2 1) 8 (6 7) 4 5", &mut map).unwrap();
+/// let whole = bf(&a, &b, unweighted).unwrap();
+/// let split = by_blocks(&a, &b, unweighted, bf).unwrap();
+/// let (w, s) = (whole.lb.unwrap(), split.lb.unwrap());
+/// assert!((w.t - s.t).abs() < lib::PRECISION);
+/// assert_eq!(w.a.len(), s.a.len());
+/// let (w, s) = (whole.ub.unwrap(), split.ub.unwrap());
+/// assert!((w.t - s.t).abs() < lib::PRECISION);
+/// ```
+pub fn by_blocks<Algo>(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    w: WeightFn,
+    algo: Algo,
+) -> Result<TauBounds>
+where
+    Algo: Fn(&PartialOrder, &PartialOrder, WeightFn) -> Result<TauBounds>,
+{
+    let blocks = blocks(rank_a, rank_b);
+    if !is_unweighted(w) || blocks.len() < 2 {
+        return algo(rank_a, rank_b, w);
+    }
+
+    let solutions = blocks
+        .iter()
+        .map(|block| algo(&block.a, &block.b, w))
+        .collect::<Result<Vec<TauBounds>>>()?;
+
+    let lb = solutions
+        .iter()
+        .map(|s| s.lb.as_ref())
+        .collect::<Option<Vec<&Bound>>>()
+        .map(|parts| recombine(rank_a, rank_b, &parts, w))
+        .transpose()?;
+    let ub = solutions
+        .iter()
+        .map(|s| s.ub.as_ref())
+        .collect::<Option<Vec<&Bound>>>()
+        .map(|parts| recombine(rank_a, rank_b, &parts, w))
+        .transpose()?;
+
+    Ok(TauBounds { lb, ub })
+}
+
+/// the arbitrations of the whole input that arbitrate every tie group the way
+/// some solution of its block does, for every combination of the solutions of
+/// the blocks.
+fn recombine(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    parts: &[&Bound],
+    w: WeightFn,
+) -> Result<Bound> {
+    ensure!(
+        parts.iter().all(|part| !part.a.is_empty()),
+        "a block has no solution"
+    );
+
+    // list at most as many of the combinations as the brute force would
+    let mut final_as = Vec::new();
+    let mut final_bs = Vec::new();
+    for choice in parts
+        .iter()
+        .map(|part| 0..part.a.len().min(part.b.len()))
+        .multi_cartesian_product()
+        .take(MAX_SOLUTIONS)
+    {
+        // the position of every tied element in the chosen solution of its block
+        let mut pos_a = BTreeMap::new();
+        let mut pos_b = BTreeMap::new();
+        for (part, i) in parts.iter().zip(choice) {
+            pos_a.extend(part.a[i].iter().enumerate().map(|(i, e)| (*e, i)));
+            pos_b.extend(part.b[i].iter().enumerate().map(|(i, e)| (*e, i)));
+        }
+        final_as.push(arbitrate(rank_a, &pos_a)?);
+        final_bs.push(arbitrate(rank_b, &pos_b)?);
+    }
+
+    // every combination of optimal solutions of the blocks is optimal
+    let count = parts
+        .iter()
+        .map(|part| part.count)
+        .try_fold(1u128, |acc, n| n.map(|n| acc.saturating_mul(n)));
+
+    // and has the same tau
+    let t = tau_auto(&final_as[0], &final_bs[0], w)?;
+    Ok(Bound {
+        a: final_as,
+        b: final_bs,
+        t,
+        count,
+        certificate: None,
+    })
+}
+
+/// sort every tie group of `rank` by the positions its elements have in the
+/// solution of their block.
+fn arbitrate(
+    rank: &PartialOrder,
+    pos: &BTreeMap<Option<Element>, usize>,
+) -> Result<StrictOrder> {
+    let mut o = StrictOrder::new_empty(rank.set_size());
+    let mut idx = 0;
+    for tg in rank {
+        let mut tgc = tg.clone();
+        if tg.len() > 1 {
+            let keys = tg
+                .iter()
+                .map(|e| {
+                    pos.get(&Some(*e)).map(|p| (*p, *e)).ok_or_else(|| {
+                        anyhow!("{e} is missing from the solution of its block")
+                    })
+                })
+                .collect::<Result<BTreeMap<usize, Element>>>()?;
+            tgc = keys.into_values().collect();
+        }
+        for elem in tgc {
+            o[idx] = Some(elem);
+            idx += 1;
+        }
+    }
+
+    debug_assert!(o.is_defined());
+    Ok(o)
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+impl Block {
+    fn empty() -> Self {
+        Self {
+            a: Vec::new(),
+            b: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! certificates of optimality for the unweighted tau bounds
+//!
+//! a pair of items whose order is fixed in both rankings (they are in different
+//! tie groups in each) is concordant, or discordant, in every arbitration. if
+//! every pair that goes against the bound (concordant for the minimum,
+//! discordant for the maximum) is such a forced pair, no other arbitration can
+//! do better, so the bound is optimal.
+//!
+//! the certificate is the list of those pairs, kept in
+//! [`Bound::certificate`]. the checker in `verifier` confirms the argument
+//! pair by pair, that the list is complete, and that it gives the claimed tau,
+//! without needing the brute force.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::bail;
+use anyhow::ensure;
+use lib::def::*;
+use lib::weights::WeightFn;
+use lib::weights::is_unweighted;
+
+use crate::bounds::tie_group_index;
+
+/// give both bounds their certificates, or an error if they can't be
+/// certified (weights, or a bound that isn't optimal).
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::weights::unweighted;
+/// # use solver::bounds::certificate::certify_bounds;
+/// # use solver::bounds::find_tau_bounds;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6)", &mut map).unwrap();
+/// let b = partial_from_string("4 (1 2 3 5) 6", &mut map).unwrap();
+/// let mut bounds = find_tau_bounds(&a, &b, unweighted).unwrap();
+/// certify_bounds(&a, &b, &mut bounds, unweighted).unwrap();
+/// // 1,2,3 are before 6, and 4 is before 5 and 6, in both
+/// let forced = bounds.lb.unwrap().certificate.unwrap();
+/// assert_eq!(forced.len(), 5);
+/// assert!(forced.contains(&('d', 'f')));
+/// // 4 is after 1,2,3 in a but before them in b
+/// let forced = bounds.ub.unwrap().certificate.unwrap();
+/// assert_eq!(forced.len(), 3);
+/// assert!(forced.iter().all(|(_, y)| *y == 'd'));
+/// ```
+pub fn certify_bounds(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    bounds: &mut TauBounds,
+    w: WeightFn,
+) -> Result<()> {
+    ensure!(
+        is_unweighted(w),
+        "only the unweighted bounds can be certified"
+    );
+    if let Some(lb) = &mut bounds.lb {
+        lb.certificate = Some(certify(rank_a, rank_b, lb, true)?);
+    }
+    if let Some(ub) = &mut bounds.ub {
+        ub.certificate = Some(certify(rank_a, rank_b, ub, false)?);
+    }
+    Ok(())
+}
+
+/// the certificate of the first arbitration of `bound`
+pub fn certify(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    bound: &Bound,
+    is_minimising: bool,
+) -> Result<Vec<(Element, Element)>> {
+    let (Some(final_a), Some(final_b)) = (bound.a.first(), bound.b.first()) else {
+        bail!("the bound has no arbitration to certify");
+    };
+    let groups_a = tie_group_index(rank_a);
+    let groups_b = tie_group_index(rank_b);
+
+    let items = final_a.ensure_defined()?.concat();
+    let pos_b = final_b
+        .ensure_defined()?
+        .concat()
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| (e, i))
+        .collect::<BTreeMap<Element, usize>>();
+
+    let mut forced = Vec::new();
+    for (i, x) in items.iter().enumerate() {
+        for y in &items[i + 1..] {
+            // x is before y in the arbitration of a
+            let concordant = pos_b[x] < pos_b[y];
+            if concordant != is_minimising {
+                continue;
+            }
+            ensure!(
+                groups_a[x] != groups_a[y] && groups_b[x] != groups_b[y],
+                "({x},{y}) goes against the bound, but could be flipped"
+            );
+            forced.push((*x, *y));
+        }
+    }
+
+    Ok(forced)
+}
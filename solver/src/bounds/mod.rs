@@ -1,27 +1,134 @@
 //! code for either bound calculation
 pub mod algo;
 pub mod bf;
+pub mod blocks;
+pub mod certificate;
+pub mod optima;
+pub mod sort;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 
 use algo::tau_bound;
 use anyhow::Result;
+use certificate::certify_bounds;
+use lib::def::Element;
 use lib::def::PartialOrder;
 use lib::def::Ranking;
 use lib::def::StrictOrder;
 use lib::def::TauBounds;
+use lib::weights::WeightFn;
+use lib::weights::is_unweighted;
+use optima::optimal_bound;
+use sort::tau_bound_unweighted;
 
-pub fn find_tau_bounds<F: Fn((usize, usize), (usize, usize)) -> f64>(
+pub fn find_tau_bounds(
     rank_a: &PartialOrder,
     rank_b: &PartialOrder,
-    w: F,
+    w: WeightFn,
 ) -> Result<TauBounds> {
-    let lb = tau_bound(rank_a, rank_b, true, &w)?;
-    let ub = tau_bound(rank_a, rank_b, false, &w)?;
+    if is_unweighted(w) {
+        // no need for the graphs, sorting is enough (and much faster)
+        let lb = tau_bound_unweighted(rank_a, rank_b, true)?;
+        let ub = tau_bound_unweighted(rank_a, rank_b, false)?;
+        return Ok(TauBounds {
+            lb: Some(lb),
+            ub: Some(ub),
+        });
+    }
+    let lb = tau_bound(rank_a, rank_b, true, w)?;
+    let ub = tau_bound(rank_a, rank_b, false, w)?;
     Ok(TauBounds {
         lb: Some(lb),
         ub: Some(ub),
     })
 }
 
+/// what to find along with the bounds, both only without weights
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extras {
+    /// list (at most) this many optimal arbitrations of each bound, and count
+    /// all of them. with weights only the one arbitration is known.
+    pub all: Option<usize>,
+    /// certify that the bounds are optimal (see [`certificate`]), which is an
+    /// error with weights
+    pub certify: bool,
+}
+
+/// same as [`find_tau_bounds`], with the `extras`.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::weights::unweighted;
+/// # use solver::bounds::Extras;
+/// # use solver::bounds::find_tau_bounds_with;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6)", &mut map).unwrap();
+/// let b = partial_from_string("(1 2 3 5) (4 6)", &mut map).unwrap();
+/// let extras = Extras { all: Some(2), certify: true };
+/// let lb = find_tau_bounds_with(&a, &b, unweighted, extras).unwrap().lb.unwrap();
+/// assert_eq!((lb.a.len(), lb.count), (2, Some(6)));
+/// assert!(lb.certificate.is_some());
+/// ```
+pub fn find_tau_bounds_with(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    w: WeightFn,
+    extras: Extras,
+) -> Result<TauBounds> {
+    let mut bounds = match extras.all {
+        Some(limit) if is_unweighted(w) => TauBounds {
+            lb: Some(optimal_bound(rank_a, rank_b, true, limit)?),
+            ub: Some(optimal_bound(rank_a, rank_b, false, limit)?),
+        },
+        _ => find_tau_bounds(rank_a, rank_b, w)?,
+    };
+    if extras.certify {
+        certify_bounds(rank_a, rank_b, &mut bounds, w)?;
+    }
+    Ok(bounds)
+}
+
+/// map every element to the index of the tie group it's in
+pub fn tie_group_index(rank: &PartialOrder) -> BTreeMap<Element, usize> {
+    rank.iter()
+        .enumerate()
+        .flat_map(|(i, tg)| tg.iter().map(move |e| (*e, i)))
+        .collect()
+}
+
+/// the order of two elements `x`,`y` of a tie group in the arbitration of a
+/// bound, given the tie groups `gx`,`gy` they are in in the other ranking: by
+/// those ascending when maximising (the pair becomes concordant) and descending
+/// when minimising (the pair becomes discordant).
+///
+/// elements tied in both rankings are ordered by label, except in the first
+/// ranking when minimising, where the reverse order makes them discordant.
+///
+/// ```
+/// # use std::cmp::Ordering;
+/// # use solver::bounds::tie_group_order;
+/// assert_eq!(tie_group_order(('a', 1), ('b', 0), false, false), Ordering::Greater);
+/// assert_eq!(tie_group_order(('a', 1), ('b', 0), true, false), Ordering::Less);
+/// assert_eq!(tie_group_order(('a', 0), ('b', 0), true, false), Ordering::Greater);
+/// assert_eq!(tie_group_order(('a', 0), ('b', 0), true, true), Ordering::Less);
+/// ```
+pub fn tie_group_order(
+    (x, gx): (Element, usize),
+    (y, gy): (Element, usize),
+    is_minimising: bool,
+    is_second: bool,
+) -> Ordering {
+    let by_group = gx.cmp(&gy);
+    if is_minimising {
+        let by_label = if is_second { x.cmp(&y) } else { y.cmp(&x) };
+        by_group.reverse().then(by_label)
+    } else {
+        by_group.then(x.cmp(&y))
+    }
+}
+
 pub fn alloc_fixed(
     final_a: &mut StrictOrder,
     final_b: &mut StrictOrder,
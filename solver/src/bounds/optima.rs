@@ -0,0 +1,156 @@
+//! all the optimal arbitrations of the unweighted tau bounds
+//!
+//! without weights, a pair that is tied in only one of the rankings must be
+//! made concordant (or discordant) in every optimal arbitration, since that can
+//! be done independently of all the other pairs. the only freedom left is
+//! within the classes of items that are tied in *both* rankings: any order of
+//! such a class is optimal, as long as the other ranking uses the same order
+//! (maximising) or the reverse one (minimising).
+//!
+//! so there are exactly `product(|class|!)` optimal arbitrations, for either
+//! bound, and they can be listed one by one from the one [`crate::bounds::sort`]
+//! finds.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use itertools::Itertools;
+use lib::def::*;
+
+use crate::bounds::sort::tau_bound_unweighted;
+use crate::bounds::tie_group_index;
+
+/// the number of optimal arbitrations of the unweighted tau bounds (the same
+/// for the minimum and the maximum). saturates at [`u128::MAX`].
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use solver::bounds::optima::count_optimal;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6)", &mut map).unwrap();
+/// let b = partial_from_string("(1 2 3 5) (4 6)", &mut map).unwrap();
+/// // (1 2 3) is tied in both, 3! ways to pick its order
+/// assert_eq!(count_optimal(&a, &b), 6);
+/// ```
+pub fn count_optimal(rank_a: &PartialOrder, rank_b: &PartialOrder) -> u128 {
+    let mul = |a: u128, b: u128| a.saturating_mul(b);
+    tied_in_both(rank_a, rank_b)
+        .iter()
+        .map(|class| (1..=(class.len() as u128)).fold(1, mul))
+        .fold(1, mul)
+}
+
+/// the unweighted tau bound, with (at most) the first `limit` of its optimal
+/// arbitrations and the number of all of them.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::tau_w::tau_knight;
+/// # use solver::bounds::optima::optimal_bound;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6)", &mut map).unwrap();
+/// let b = partial_from_string("(1 2 3 5) (4 6)", &mut map).unwrap();
+/// let bound = optimal_bound(&a, &b, true, 100).unwrap();
+/// assert_eq!(bound.count, Some(6));
+/// assert_eq!(bound.a.len(), 6);
+/// for (x, y) in bound.a.iter().zip(bound.b.iter()) {
+///     assert!((tau_knight(x, y).unwrap() - bound.t).abs() < lib::PRECISION);
+/// }
+/// ```
+pub fn optimal_bound(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    is_minimising: bool,
+    limit: usize,
+) -> Result<Bound> {
+    let first = tau_bound_unweighted(rank_a, rank_b, is_minimising)?;
+    let (a, b) = optimal_arbitrations(rank_a, rank_b, &first, is_minimising)
+        .take(limit)
+        .unzip();
+    Ok(Bound {
+        t: first.t,
+        a,
+        b,
+        count: Some(count_optimal(rank_a, rank_b)),
+        certificate: None,
+    })
+}
+
+/// lazily list all the optimal arbitrations, starting from (the first solution
+/// of) `first`, which must be optimal.
+pub fn optimal_arbitrations(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    first: &Bound,
+    is_minimising: bool,
+) -> impl Iterator<Item = (StrictOrder, StrictOrder)> {
+    let (first_a, first_b) = (first.a[0].clone(), first.b[0].clone());
+    let pos_a = positions(&first_a);
+    let pos_b = positions(&first_b);
+
+    // every class takes up the same slots in every optimal arbitration, only
+    // the order of its items in them changes.
+    let classes = tied_in_both(rank_a, rank_b)
+        .into_iter()
+        .map(|class| {
+            let slots_a = class.iter().map(|e| pos_a[e]).sorted().collect_vec();
+            let slots_b = class.iter().map(|e| pos_b[e]).sorted().collect_vec();
+            let items = slots_a.iter().map(|i| first_a[*i].unwrap()).collect_vec();
+            (slots_a, slots_b, items)
+        })
+        .collect_vec();
+
+    // with no classes, the product below would be empty
+    let orders: Box<dyn Iterator<Item = Vec<Vec<Element>>>> = if classes.is_empty() {
+        Box::new(std::iter::once(Vec::new()))
+    } else {
+        Box::new(
+            classes
+                .iter()
+                .map(|(_, _, items)| items.clone().into_iter().permutations(items.len()))
+                .collect_vec()
+                .into_iter()
+                .multi_cartesian_product(),
+        )
+    };
+
+    orders.map(move |perms| {
+        let mut a = first_a.clone();
+        let mut b = first_b.clone();
+        for ((slots_a, slots_b, _), perm) in classes.iter().zip(perms) {
+            for (i, e) in slots_a.iter().zip(&perm) {
+                a[*i] = Some(*e);
+            }
+            // the same order in b when maximising, the reverse when minimising
+            let in_b: Box<dyn Iterator<Item = &Element>> = if is_minimising {
+                Box::new(perm.iter().rev())
+            } else {
+                Box::new(perm.iter())
+            };
+            for (i, e) in slots_b.iter().zip(in_b) {
+                b[*i] = Some(*e);
+            }
+        }
+        (a, b)
+    })
+}
+
+/// the classes of (more than one) items tied in both rankings
+fn tied_in_both(rank_a: &PartialOrder, rank_b: &PartialOrder) -> Vec<Vec<Element>> {
+    let groups_b = tie_group_index(rank_b);
+    let mut classes: BTreeMap<(usize, usize), Vec<Element>> = BTreeMap::new();
+    for (i, tg) in rank_a.iter().enumerate().filter(|(_, tg)| tg.len() > 1) {
+        for e in tg {
+            classes.entry((i, groups_b[e])).or_default().push(*e);
+        }
+    }
+    classes.into_values().filter(|c| c.len() > 1).collect()
+}
+
+fn positions(o: &StrictOrder) -> BTreeMap<Element, usize> {
+    o.iter()
+        .enumerate()
+        .filter_map(|(i, e)| e.map(|e| (e, i)))
+        .collect()
+}
@@ -0,0 +1,91 @@
+//! sort-based bound calculation for the unweighted tau, in O(n log n)
+//!
+//! without weights every pair that is tied in at least one of the rankings can
+//! be made concordant (or discordant) independently of all the others, so there
+//! is no need for the graphs of [`crate::bounds::algo`]: it is enough to sort
+//! every tie group by the tie groups of the other ranking.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::ensure;
+use lib::def::*;
+use lib::tau_w::tau_knight;
+
+use crate::bounds::tie_group_index;
+use crate::bounds::tie_group_order;
+
+/// same as [`crate::bounds::algo::tau_bound`] with the [`lib::weights::unweighted`]
+/// weight, but in O(n log n).
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use lib::def::partial_from_string;
+/// # use lib::weights::unweighted;
+/// # use solver::bounds::algo::tau_bound;
+/// # use solver::bounds::sort::tau_bound_unweighted;
+/// let mut map = BTreeMap::new();
+/// let a = partial_from_string("(1 2 3) 4 (5 6) 7", &mut map).unwrap();
+/// let b = partial_from_string("(4 5) 1 (2 3 7 6)", &mut map).unwrap();
+/// for is_minimising in [true, false] {
+///     let fast = tau_bound_unweighted(&a, &b, is_minimising).unwrap();
+///     let graph = tau_bound(&a, &b, is_minimising, unweighted).unwrap();
+///     assert!((fast.t - graph.t).abs() < lib::PRECISION);
+/// }
+/// ```
+pub fn tau_bound_unweighted(
+    rank_a: &PartialOrder,
+    rank_b: &PartialOrder,
+    is_minimising: bool,
+) -> Result<Bound> {
+    // checks
+    let (length, _item_set) = rank_a.ensure_conjoint(rank_b)?;
+
+    ensure!(
+        length >= 2,
+        "ranks are too short ({}): {rank_a:?}/{rank_b:?}",
+        length
+    );
+
+    let groups_a = tie_group_index(rank_a);
+    let groups_b = tie_group_index(rank_b);
+
+    let final_a = arbitrate(rank_a, &groups_b, is_minimising, false);
+    let final_b = arbitrate(rank_b, &groups_a, is_minimising, true);
+
+    let t = tau_knight(&final_a, &final_b)?;
+    Ok(Bound {
+        a: vec![final_a],
+        b: vec![final_b],
+        t,
+        count: None,
+        certificate: None,
+    })
+}
+
+/// the linear extension of `rank` where each tie group is sorted by
+/// [`tie_group_order`]. this is the same arbitration as the one of
+/// [`crate::bounds::algo`].
+fn arbitrate(
+    rank: &PartialOrder,
+    other_groups: &BTreeMap<Element, usize>,
+    is_minimising: bool,
+    is_second: bool,
+) -> StrictOrder {
+    let mut o = StrictOrder::new_empty(rank.set_size());
+    let mut idx = 0;
+    for tg in rank {
+        // look up the keys once, not in every comparison
+        let mut keyed = tg
+            .iter()
+            .map(|e| (*e, other_groups[e]))
+            .collect::<Vec<(Element, usize)>>();
+        keyed.sort_by(|x, y| tie_group_order(*x, *y, is_minimising, is_second));
+        for (elem, _) in keyed {
+            o[idx] = Some(elem);
+            idx += 1;
+        }
+    }
+
+    debug_assert!(o.is_defined());
+    o
+}
@@ -9,6 +9,7 @@ use lib::def::Element;
 use lib::def::PartialOrder;
 use lib::def::TauBounds;
 use lib::def::partial_from_string;
+use lib::weights::WeightFn;
 use lib::weights::ap_high_weight;
 use lib::weights::ap_weight;
 use lib::weights::const_weight_42;
@@ -28,21 +29,25 @@ use lib::weights::weight_left;
 use lib::weights::weight_right;
 use lib::weights::weight_sum;
 use lib::weights::weight_zero;
+use solver::bounds::Extras;
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
     pub a: String,
     pub b: String,
+    /// list (up to) this many optimal arbitrations of each bound, and how many
+    /// there are in total
+    #[arg(long)]
+    pub all: Option<usize>,
+    /// also print certificates that the bounds are optimal (without weights)
+    #[arg(long)]
+    pub certify: bool,
 }
 
 pub fn compute<Algo>(algo: Algo) -> Result<()>
 where
-    Algo: Fn(
-        &PartialOrder,
-        &PartialOrder,
-        fn((usize, usize), (usize, usize)) -> f64,
-    ) -> Result<TauBounds>,
+    Algo: Fn(&PartialOrder, &PartialOrder, WeightFn, Extras) -> Result<TauBounds>,
 {
     let args = Cli::parse();
 
@@ -97,7 +102,11 @@ where
     let w = hyperbolic_mult_weight;
     let w = unweighted;
 
-    let bounds = match algo(&rank_a, &rank_b, w) {
+    let extras = Extras {
+        all: args.all,
+        certify: args.certify,
+    };
+    let bounds = match algo(&rank_a, &rank_b, w, extras) {
         Ok(sol) => sol,
         Err(e) => {
             if format!("{e}").contains("skipped") {
@@ -0,0 +1,200 @@
+//! differential testing of [`find_tau_bounds`] against the brute force
+//!
+//! generates random pairs of rankings with ties, solves them both ways, and
+//! shrinks the first input they disagree on (see [`lib::shrink`]). the inputs
+//! are small enough for the brute force, and the same seed always gives the
+//! same inputs, so a counterexample can be found again. [`lib::rwt::all_pairs`]
+//! goes over every input of a size instead.
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use anyhow::Result;
+use lib::PRECISION;
+use lib::def::*;
+use lib::rng::Rng;
+use lib::shrink::shrink_orders;
+use lib::tau_w::tau_auto;
+use lib::weights::WeightFn;
+
+use crate::bounds::bf::tau_bounds_bf;
+use crate::bounds::find_tau_bounds;
+
+/// what the generated inputs look like
+#[derive(Debug, Clone)]
+pub struct Shape {
+    /// the number of items of an input
+    pub items: RangeInclusive<usize>,
+    /// the chance that an item is tied with the one before it
+    pub tie: f64,
+    /// the most items in one tie group
+    pub max_group: usize,
+}
+
+/// an input the solver and the brute force disagree on, with the (tmin, tmax)
+/// of both.
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+    pub a: PartialOrder,
+    pub b: PartialOrder,
+    pub solver: (f64, f64),
+    pub reference: (f64, f64),
+}
+
+/// look for an input of `shape` that [`find_tau_bounds`] gets wrong with the
+/// weight `w`, trying (at most) `cases` of them, and shrink it. inputs where
+/// only one side has a tau (see [`Counterexample::is_undefined`]) aren't
+/// wrong.
+///
+/// ```
+/// # use lib::weights::unweighted;
+/// # use solver::differential::Shape;
+/// # use solver::differential::find_counterexample;
+/// let found = find_counterexample(unweighted, &Shape::default(), 200, 1).unwrap();
+/// assert!(found.is_none());
+/// ```
+pub fn find_counterexample(
+    w: WeightFn,
+    shape: &Shape,
+    cases: usize,
+    seed: u64,
+) -> Result<Option<Counterexample>> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..cases {
+        let (a, b) = random_pair(&mut rng, shape);
+        if !is_wrong(disagreement(&a, &b, w)) {
+            continue;
+        }
+        // inputs the solver can't take (too few items) don't count
+        let (a, b) = shrink_orders(&a, &b, |x, y| Ok(is_wrong(disagreement(x, y, w))))?;
+        return disagreement(&a, &b, w);
+    }
+    Ok(None)
+}
+
+fn is_wrong(d: Result<Option<Counterexample>>) -> bool {
+    d.is_ok_and(|d| d.is_some_and(|c| !c.is_undefined()))
+}
+
+/// solve `a`,`b` both ways, and compare the bounds. inputs too big for the
+/// brute force never disagree.
+pub fn disagreement(
+    a: &PartialOrder,
+    b: &PartialOrder,
+    w: WeightFn,
+) -> Result<Option<Counterexample>> {
+    let reference = match tau_bounds_bf(a, b, |x, y| tau_auto(x, y, w)) {
+        Ok(bounds) => bounds,
+        Err(e) if e.to_string().starts_with("skipped") => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let solver = find_tau_bounds(a, b, w)?;
+
+    let t = |bounds: &TauBounds| {
+        (
+            bounds.lb.as_ref().map_or(f64::NAN, |lb| lb.t),
+            bounds.ub.as_ref().map_or(f64::NAN, |ub| ub.t),
+        )
+    };
+    let (solver, reference) = (t(&solver), t(&reference));
+    // with zero weights tau can be undefined, which the brute force and the
+    // solver show differently
+    let agree =
+        |x: f64, y: f64| (x - y).abs() <= PRECISION || !(x.is_finite() || y.is_finite());
+    if agree(solver.0, reference.0) && agree(solver.1, reference.1) {
+        return Ok(None);
+    }
+    Ok(Some(Counterexample {
+        a: a.clone(),
+        b: b.clone(),
+        solver,
+        reference,
+    }))
+}
+
+/// a random pair of rankings of the same items, of `shape`. the items are
+/// named in the order they first appear in a, as the solvers see them (see
+/// [`lib::rwt::all_pairs`]).
+///
+/// ```
+/// # use lib::def::Ranking;
+/// # use lib::rng::Rng;
+/// # use solver::differential::Shape;
+/// # use solver::differential::random_pair;
+/// let shape = Shape { items: 5..=5, tie: 0.5, max_group: 2 };
+/// let (a, b) = random_pair(&mut Rng::new(7), &shape);
+/// assert_eq!((a.set_size(), b.set_size()), (5, 5));
+/// assert!(a.iter().chain(&b).all(|tg| tg.len() <= 2));
+/// ```
+pub fn random_pair(rng: &mut Rng, shape: &Shape) -> (PartialOrder, PartialOrder) {
+    let (lo, hi) = (*shape.items.start(), *shape.items.end());
+    let n = lo + rng.below(hi.saturating_sub(lo) + 1);
+    let (a, b) = (random_ranking(rng, shape, n), random_ranking(rng, shape, n));
+
+    let names = a
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, e)| (*e, element_from_index(i)))
+        .collect::<BTreeMap<Element, Element>>();
+    let rename = |r: PartialOrder| -> PartialOrder {
+        r.into_iter()
+            .map(|tg| tg.into_iter().map(|e| names[&e]).collect())
+            .collect()
+    };
+    (rename(a), rename(b))
+}
+
+fn random_ranking(rng: &mut Rng, shape: &Shape, n: usize) -> PartialOrder {
+    // fisher-yates
+    let mut items = (0..n).map(element_from_index).collect::<Vec<Element>>();
+    for i in (1..n).rev() {
+        items.swap(i, rng.below(i + 1));
+    }
+
+    let mut rank: PartialOrder = Vec::new();
+    for e in items {
+        match rank.last_mut() {
+            Some(tg) if tg.len() < shape.max_group && rng.chance(shape.tie) => tg.push(e),
+            _ => rank.push(vec![e]),
+        }
+    }
+    rank
+}
+
+impl Counterexample {
+    /// does only one of them have a tau? a weight that is zero or infinite
+    /// somewhere makes tau 0/0 or inf/inf for some arbitrations, which the
+    /// solver and the brute force run into differently. that says nothing
+    /// about whether the solver is exact.
+    pub fn is_undefined(&self) -> bool {
+        [self.solver, self.reference]
+            .iter()
+            .any(|(tmin, tmax)| !(tmin.is_finite() && tmax.is_finite()))
+    }
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self {
+            items: 2..=7,
+            tie: 0.4,
+            max_group: 4,
+        }
+    }
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a: {}\nb: {}\ntmin: {} (brute force {})\ntmax: {} (brute force {})",
+            partial_to_string(&self.a),
+            partial_to_string(&self.b),
+            self.solver.0,
+            self.reference.0,
+            self.solver.1,
+            self.reference.1,
+        )
+    }
+}
@@ -0,0 +1,126 @@
+//! is the graph algorithm exact for a weight?
+//!
+//! goes over every input of up to `n` items (see
+//! [`lib::rwt::all_pairs`]), and compares [`find_tau_bounds`] to
+//! the brute force on each. reports, per size, how many inputs it gets wrong
+//! and by how much, and the first (smallest) counterexample.
+//!
+//! the weight is the name of one in [`lib::weights`], or an expression (see
+//! [`lib::expr`]).
+//!
+//! [`find_tau_bounds`]: solver::bounds::find_tau_bounds
+use std::time::Instant;
+
+use anyhow::Result;
+use clap::Parser;
+use clap_derive::Parser;
+use lib::expr::expression_weight;
+use lib::rwt::all_pairs;
+use lib::weights::by_name;
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+use solver::differential::Counterexample;
+use solver::differential::disagreement;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// a weight name, or an expression in xa, xb, ya, yb
+    pub weight: String,
+    /// the most items an input has
+    #[arg(default_value_t = 5)]
+    pub n: usize,
+}
+
+/// how far off the graph bounds are, over some inputs
+#[derive(Debug, Default)]
+struct Gaps {
+    inputs: usize,
+    /// the bound is wrong, (tmin, tmax)
+    wrong: (usize, usize),
+    /// one side is undefined (zero weights) and the other isn't
+    undefined: usize,
+    max: (f64, f64),
+    sum: (f64, f64),
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let w = by_name(&args.weight).or_else(|_| expression_weight(&args.weight))?;
+
+    let start = Instant::now();
+    let mut smallest: Option<Counterexample> = None;
+    let mut undefined: Option<Counterexample> = None;
+    for n in 2..=args.n {
+        let found = all_pairs(n)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(a, b)| disagreement(&a, &b, w))
+            .collect::<Result<Vec<Option<Counterexample>>>>()?;
+
+        let mut gaps = Gaps {
+            inputs: found.len(),
+            ..Default::default()
+        };
+        for c in found.iter().flatten() {
+            gaps.add(c);
+        }
+        println!("n={n}: {gaps}");
+
+        let (wrong, undef): (Vec<_>, Vec<_>) =
+            found.into_iter().flatten().partition(|c| !c.is_undefined());
+        smallest = smallest.or(wrong.into_iter().next());
+        undefined = undefined.or(undef.into_iter().next());
+    }
+
+    match smallest {
+        None => println!("exact for every input of up to {} items", args.n),
+        Some(c) => println!("smallest counterexample:\n{c}"),
+    }
+    if let Some(c) = undefined {
+        println!("smallest input where only one of them has a tau:\n{c}");
+    }
+    println!("total time: {}s", start.elapsed().as_secs_f32());
+    Ok(())
+}
+
+impl Gaps {
+    fn add(&mut self, c: &Counterexample) {
+        if c.is_undefined() {
+            self.undefined += 1;
+            return;
+        }
+        // the graph bounds come from actual arbitrations, so they can only be
+        // too high (tmin) or too low (tmax)
+        let dmin = c.solver.0 - c.reference.0;
+        let dmax = c.reference.1 - c.solver.1;
+        if dmin > lib::PRECISION {
+            self.wrong.0 += 1;
+            self.max.0 = self.max.0.max(dmin);
+            self.sum.0 += dmin;
+        }
+        if dmax > lib::PRECISION {
+            self.wrong.1 += 1;
+            self.max.1 = self.max.1.max(dmax);
+            self.sum.1 += dmax;
+        }
+    }
+}
+
+impl std::fmt::Display for Gaps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mean = |sum: f64, n: usize| if n == 0 { 0.0 } else { sum / n as f64 };
+        write!(
+            f,
+            "{} inputs, tmin wrong on {} (max gap {:.4}, mean {:.4}), tmax wrong on {} (max gap {:.4}, mean {:.4}), {} undefined",
+            self.inputs,
+            self.wrong.0,
+            self.max.0,
+            mean(self.sum.0, self.wrong.0),
+            self.wrong.1,
+            self.max.1,
+            mean(self.sum.1, self.wrong.1),
+            self.undefined,
+        )
+    }
+}
@@ -1 +1,3 @@
 pub mod bounds;
+pub mod differential;
+pub mod render;
@@ -1,10 +1,10 @@
 //! calculation of $\tau_{min}, \tau_{max}$
 use anyhow::Result;
 use cli::compute;
-use solver::bounds::find_tau_bounds;
+use solver::bounds::find_tau_bounds_with;
 
 mod cli;
 
 fn main() -> Result<()> {
-    compute(find_tau_bounds)
+    compute(find_tau_bounds_with)
 }
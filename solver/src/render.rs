@@ -0,0 +1,291 @@
+//! a page that shows why an input has the bounds it has
+//!
+//! the input and the arbitrations of `tau_min` and `tau_max` are each drawn as
+//! two columns, one per ranking, with a line from every item on the left to the
+//! same item on the right: the lines that cross are the discordant pairs. tie
+//! groups are shaded, in the arbitrations too (they fill the same places). the
+//! items of the pairs that are concordant in one arbitration but discordant in
+//! the other are the ones whose lines are highlighted, and the pairs are listed
+//! below. the page is a single html file with the svg inline.
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::ops::Range;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use lib::def::Bound;
+use lib::def::Element;
+use lib::def::PartialOrder;
+use lib::def::StrictOrder;
+use lib::def::TauBounds;
+use lib::tau_w::TauVariants;
+use lib::tau_w::tau_partial_auto;
+use lib::weights::WeightFn;
+
+/// pixels between two items of a column
+const ROW: usize = 28;
+/// where the columns are, and where their lines start and end
+const LEFT: usize = 60;
+const RIGHT: usize = 300;
+const GAP: usize = 30;
+const TOP: usize = 40;
+
+const HIGHLIGHT: &str = "#d02f42";
+const PLAIN: &str = "#9a9aa8";
+const TIE: &str = "#e4dcf4";
+
+/// a ranking as it's drawn: the items from the top, and the places of each tie
+/// group of more than one item
+struct Column {
+    items: Vec<Element>,
+    ties: Vec<Range<usize>>,
+}
+
+/// are `x` and `y` in the same order in `a` and `b`?
+///
+/// ```
+/// # use solver::render::concordant;
+/// let a = vec![Some('a'), Some('b'), Some('c')];
+/// let b = vec![Some('b'), Some('a'), Some('c')];
+/// assert!(!concordant(&a, &b, 'a', 'b'));
+/// assert!(concordant(&a, &b, 'a', 'c'));
+/// ```
+pub fn concordant(a: &StrictOrder, b: &StrictOrder, x: Element, y: Element) -> bool {
+    let at = |o: &StrictOrder, e: Element| o.iter().position(|p| *p == Some(e));
+    (at(a, x) < at(a, y)) == (at(b, x) < at(b, y))
+}
+
+/// the pairs that are concordant in one of the arbitrations and discordant in
+/// the other
+///
+/// ```
+/// # use solver::render::flipped_pairs;
+/// let min = (vec![Some('a'), Some('b'), Some('c')], vec![Some('b'), Some('a'), Some('c')]);
+/// let max = (vec![Some('a'), Some('b'), Some('c')], vec![Some('a'), Some('b'), Some('c')]);
+/// assert_eq!(flipped_pairs(&min, &max), vec![('a', 'b')]);
+/// ```
+pub fn flipped_pairs(
+    min: &(StrictOrder, StrictOrder),
+    max: &(StrictOrder, StrictOrder),
+) -> Vec<(Element, Element)> {
+    let items = min.0.iter().flatten().copied().collect::<BTreeSet<_>>();
+    let mut flipped = Vec::new();
+    for &x in &items {
+        for &y in items.range(x..).skip(1) {
+            if concordant(&min.0, &min.1, x, y) != concordant(&max.0, &max.1, x, y) {
+                flipped.push((x, y));
+            }
+        }
+    }
+    flipped
+}
+
+/// the page for the input `a`, `b` with these bounds, under the weight `w`
+/// called `weight`. `names` are the names the items were given as.
+pub fn render(
+    a: &PartialOrder,
+    b: &PartialOrder,
+    bounds: &TauBounds,
+    names: &BTreeMap<Element, String>,
+    weight: &str,
+    w: WeightFn,
+) -> Result<String> {
+    let name = |e: &Element| escape(names.get(e).map_or("?", String::as_str));
+    let ranking = |p: &PartialOrder| {
+        p.iter()
+            .map(|g| match g.as_slice() {
+                [e] => name(e),
+                _ => format!("({})", g.iter().map(name).collect::<Vec<_>>().join(" ")),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let first = |bound: &Option<Bound>, which: &str| {
+        bound
+            .as_ref()
+            .and_then(|b| {
+                Some((b.t, (b.a.first()?.clone(), b.b.first()?.clone()), b.count))
+            })
+            .ok_or_else(|| anyhow!("the solver found no arbitration for {which}"))
+    };
+    let (tmin, min, nmin) = first(&bounds.lb, "tau_min")?;
+    let (tmax, max, nmax) = first(&bounds.ub, "tau_max")?;
+    let tau_b = tau_partial_auto(a, b, w, TauVariants::B)?;
+
+    let flipped = flipped_pairs(&min, &max);
+    let highlighted = flipped
+        .iter()
+        .flat_map(|(x, y)| [*x, *y])
+        .collect::<BTreeSet<_>>();
+
+    let mut page = String::new();
+    writeln!(
+        page,
+        "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} / {}</title>",
+        ranking(a),
+        ranking(b)
+    )?;
+    writeln!(
+        page,
+        "<style>\nbody {{ font-family: sans-serif; margin: 2em; color: #222; }}\n\
+         .panels {{ display: flex; flex-wrap: wrap; gap: 2em; }}\n\
+         figure {{ margin: 0; }}\n\
+         figcaption {{ font-weight: bold; text-align: center; }}\n\
+         td, th {{ padding: 0.2em 0.8em; text-align: left; }}\n</style>\n</head>\n<body>"
+    )?;
+    writeln!(page, "<h1>{} / {}</h1>", ranking(a), ranking(b))?;
+    writeln!(
+        page,
+        "<p>weight <code>{}</code>: τ-b {tau_b:.4}, τ-min {tmin:.4}, τ-max {tmax:.4}, \
+         width {:.4}</p>",
+        escape(weight),
+        tmax - tmin
+    )?;
+    let count = |n: Option<u128>| n.map_or(String::new(), |n| format!(", one of {n}"));
+
+    writeln!(page, "<div class=\"panels\">")?;
+    let input = (column_of_partial(a), column_of_partial(b));
+    page.push_str(&panel("input", &input, &highlighted, &name));
+    let min_columns = (
+        column_of_strict(&min.0, &input.0),
+        column_of_strict(&min.1, &input.1),
+    );
+    let caption = format!("τ-min arbitration{}", count(nmin));
+    page.push_str(&panel(&caption, &min_columns, &highlighted, &name));
+    let max_columns = (
+        column_of_strict(&max.0, &input.0),
+        column_of_strict(&max.1, &input.1),
+    );
+    let caption = format!("τ-max arbitration{}", count(nmax));
+    page.push_str(&panel(&caption, &max_columns, &highlighted, &name));
+    writeln!(page, "</div>")?;
+
+    writeln!(page, "<h2>pairs that flip ({} of them)</h2>", flipped.len())?;
+    if !flipped.is_empty() {
+        writeln!(
+            page,
+            "<table>\n<tr><th>pair</th><th>in τ-min</th><th>in τ-max</th></tr>"
+        )?;
+        let order = |(a, b): &(StrictOrder, StrictOrder), x, y| match concordant(a, b, x, y)
+        {
+            true => "concordant",
+            false => "discordant",
+        };
+        for &(x, y) in &flipped {
+            writeln!(
+                page,
+                "<tr><td>{} {}</td><td>{}</td><td>{}</td></tr>",
+                name(&x),
+                name(&y),
+                order(&min, x, y),
+                order(&max, x, y)
+            )?;
+        }
+        writeln!(page, "</table>")?;
+    }
+    writeln!(page, "</body>\n</html>")?;
+    Ok(page)
+}
+
+fn column_of_partial(p: &PartialOrder) -> Column {
+    let mut ties = Vec::new();
+    let mut at = 0;
+    for group in p {
+        if group.len() > 1 {
+            ties.push(at..at + group.len());
+        }
+        at += group.len();
+    }
+    Column {
+        items: p.iter().flatten().copied().collect(),
+        ties,
+    }
+}
+
+/// an arbitration, in the places of the tie groups of the input it's of
+fn column_of_strict(o: &StrictOrder, input: &Column) -> Column {
+    Column {
+        items: o.iter().flatten().copied().collect(),
+        ties: input.ties.clone(),
+    }
+}
+
+/// the svg of the two columns, with a line for every item
+fn panel(
+    caption: &str,
+    (left, right): &(Column, Column),
+    highlighted: &BTreeSet<Element>,
+    name: &impl Fn(&Element) -> String,
+) -> String {
+    let rows = left.items.len().max(right.items.len());
+    let height = TOP + rows * ROW;
+    let width = RIGHT + LEFT;
+    let y = |i: usize| TOP + i * ROW + ROW / 2;
+    let mut svg = format!(
+        "<figure>\n<figcaption>{caption}</figcaption>\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         font-size=\"15\">\n"
+    );
+    svg.push_str(&format!(
+        "<text x=\"{LEFT}\" y=\"20\" text-anchor=\"middle\" font-weight=\"bold\">a</text>\n\
+         <text x=\"{RIGHT}\" y=\"20\" text-anchor=\"middle\" font-weight=\"bold\">b</text>\n"
+    ));
+    for (x, column) in [(LEFT, left), (RIGHT, right)] {
+        for tie in &column.ties {
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"8\" fill=\"{TIE}\"/>\n",
+                x - GAP + 4,
+                TOP + tie.start * ROW + 2,
+                2 * GAP - 8,
+                tie.len() * ROW - 4
+            ));
+        }
+    }
+    let place = |c: &Column| {
+        c.items
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (*e, i))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (on_left, on_right) = (place(left), place(right));
+    // the plain ones first, so the highlighted ones are drawn over them
+    let mut lines = on_left
+        .iter()
+        .filter_map(|(e, i)| Some((*e, *i, *on_right.get(e)?)))
+        .collect::<Vec<_>>();
+    lines.sort_by_key(|(e, _, _)| highlighted.contains(e));
+    for (e, i, j) in lines {
+        let (colour, stroke) = match highlighted.contains(&e) {
+            true => (HIGHLIGHT, 2.5),
+            false => (PLAIN, 1.5),
+        };
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{colour}\" \
+             stroke-width=\"{stroke}\"/>\n",
+            LEFT + GAP,
+            y(i),
+            RIGHT - GAP,
+            y(j)
+        ));
+    }
+    for (x, column) in [(LEFT, left), (RIGHT, right)] {
+        for (i, e) in column.items.iter().enumerate() {
+            svg.push_str(&format!(
+                "<text x=\"{x}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>\n",
+                y(i),
+                name(e)
+            ));
+        }
+    }
+    svg.push_str("</svg>\n</figure>\n");
+    svg
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
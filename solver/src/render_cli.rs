@@ -0,0 +1,41 @@
+//! write a page that shows the bounds of one input (see [`solver::render`])
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use clap_derive::Parser;
+use lib::def::Element;
+use lib::def::partial_from_string;
+use lib::expr::expression_weight;
+use lib::weights::by_name;
+use solver::bounds::find_tau_bounds;
+use solver::render::render;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    pub a: String,
+    pub b: String,
+    /// where the html goes
+    pub output: PathBuf,
+    /// a weight name, or an expression in xa, xb, ya, yb
+    #[arg(long, default_value = "unweighted")]
+    pub weight: String,
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let w = by_name(&args.weight).or_else(|_| expression_weight(&args.weight))?;
+
+    let mut inp_map: BTreeMap<String, Element> = BTreeMap::new();
+    let rank_a = partial_from_string(&args.a, &mut inp_map)?;
+    let rank_b = partial_from_string(&args.b, &mut inp_map)?;
+    let names = inp_map.into_iter().map(|(s, e)| (e, s)).collect();
+
+    let bounds = find_tau_bounds(&rank_a, &rank_b, w)?;
+    let page = render(&rank_a, &rank_b, &bounds, &names, &args.weight, w)?;
+    std::fs::write(&args.output, page)?;
+    println!("{}", args.output.display());
+    Ok(())
+}
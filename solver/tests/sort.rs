@@ -0,0 +1,30 @@
+//! [`tau_bound_unweighted`] against the graph algorithm it replaces without
+//! weights, on every distinct pair of rankings of up to 5 elements.
+//!
+//! [`tau_bound_unweighted`]: solver::bounds::sort::tau_bound_unweighted
+use lib::PRECISION;
+use lib::def::partial_to_string;
+use lib::rwt::distinct_pairs;
+use lib::weights::unweighted;
+use solver::bounds::algo::tau_bound;
+use solver::bounds::sort::tau_bound_unweighted;
+
+#[test]
+fn same_bounds_as_the_graphs() {
+    for n in 2..=5 {
+        for (a, b) in distinct_pairs(n) {
+            for is_minimising in [true, false] {
+                let fast = tau_bound_unweighted(&a, &b, is_minimising).unwrap();
+                let graph = tau_bound(&a, &b, is_minimising, unweighted).unwrap();
+                assert!(
+                    (fast.t - graph.t).abs() < PRECISION,
+                    "{} / {} (minimising: {is_minimising}): {} by sorting, {} by the graphs",
+                    partial_to_string(&a),
+                    partial_to_string(&b),
+                    fast.t,
+                    graph.t
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! regression tests of [`find_tau_bounds`] against the brute force, one per
+//! weight in [`lib::weights`].
+//!
+//! for the weights it's exact for, a few hundred random inputs must agree. for
+//! the others, the (shrunk) counterexample the harness found must still be one,
+//! until the solver learns to handle it.
+//!
+//! [`find_tau_bounds`]: solver::bounds::find_tau_bounds
+use std::collections::BTreeMap;
+
+use lib::def::partial_from_string;
+use lib::weights::*;
+use solver::differential::Shape;
+use solver::differential::disagreement;
+use solver::differential::find_counterexample;
+
+const CASES: usize = 400;
+const SEED: u64 = 2;
+
+macro_rules! exact {
+    ($($w:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $w() {
+                let found = find_counterexample(super::$w, &Shape::default(), CASES, SEED).unwrap();
+                assert!(found.is_none(), "{}", found.unwrap());
+            }
+        )*
+        pub const NAMES: &[&str] = &[$(stringify!($w)),*];
+    };
+}
+
+macro_rules! inexact {
+    ($($w:ident: $a:literal, $b:literal;)*) => {
+        $(
+            #[test]
+            fn $w() {
+                let mut map = BTreeMap::new();
+                let a = partial_from_string($a, &mut map).unwrap();
+                let b = partial_from_string($b, &mut map).unwrap();
+                let found = disagreement(&a, &b, super::$w).unwrap();
+                assert!(found.is_some_and(|c| !c.is_undefined()));
+            }
+        )*
+        pub const NAMES: &[&str] = &[$(stringify!($w)),*];
+    };
+}
+
+mod exact {
+    use super::*;
+
+    exact!(
+        unweighted,
+        hyperbolic_addtv_weight,
+        ap_weight,
+        ap_high_weight,
+        const_weight_42,
+        weight_zero,
+        weight_inv_log,
+        weight_inv_left,
+        rbo_weight,
+    );
+}
+
+mod inexact {
+    use super::*;
+
+    inexact! {
+        hyperbolic_mult_weight: "a f (d b)", "d a f b";
+        hyperbolic_sym_mult_weight: "g f (b d) a c e", "c e a b g f d";
+        hyper_left_weight: "a (b c)", "a (c b)";
+        weight_inv_right: "a (b c)", "(b c) a";
+        weight_right: "a (b c)", "a (b c)";
+        weight_left: "(a b) c", "(a c) b";
+        weight_sum: "(e c) a b", "c a b e";
+        threshold_bin_weight: "d f a e (b c)", "e f d a (c b)";
+        threshold_weight: "a f (d b)", "d a f b";
+        rbo_other_weight: "a f (d b)", "d a f b";
+        expo_thresh_weight: "a f (d b)", "d a f b";
+    }
+}
+
+#[test]
+fn every_weight_is_covered() {
+    for (name, _) in NAMED {
+        assert!(
+            exact::NAMES.contains(name) || inexact::NAMES.contains(name),
+            "no regression test for {name}"
+        );
+    }
+}
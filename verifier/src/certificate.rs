@@ -0,0 +1,132 @@
+//! checking the optimality certificates of the (unweighted) solver
+//!
+//! this deliberately doesn't use anything from `solver`: a certificate lists
+//! the pairs going against the bound in the given arbitration, and claims that
+//! every one of them is forced by the input. if that's true, no arbitration can
+//! do better, so the bound is optimal, and we only need the input to check it.
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use lib::PRECISION;
+use lib::def::*;
+
+use crate::consistency::extension_of;
+use crate::consistency::parse_arbitration;
+
+/// check the certificate `forced` (pairs of names, in the order of `sol` in
+/// a) of the arbitration `sol` of the input `a`,`b` that claims tau `t`. every
+/// pair in it must go against the bound and be forced, and it must have every
+/// pair that goes against the bound. runs in quadratic time.
+///
+/// ```
+/// # use verifier::certificate::check_certificate;
+/// let (a, b) = ("(1 2 3) 4 (5 6)", "4 (1 2 3 5) 6");
+/// let sol = ("3 2 1 4 6 5", "4 5 1 2 3 6");
+/// let t = -1.0 / 3.0;
+/// let pairs = |s: &[(&str, &str)]| {
+///     s.iter().map(|(x, y)| (x.to_string(), y.to_string())).collect::<Vec<_>>()
+/// };
+/// let forced = pairs(&[("3", "6"), ("2", "6"), ("1", "6"), ("4", "6"), ("4", "5")]);
+/// assert!(check_certificate(a, b, sol, &forced, t, true).is_ok());
+/// // a missing pair, one that doesn't go against the bound, or one that
+/// // could still be flipped
+/// assert!(check_certificate(a, b, sol, &forced[1..], t, true).is_err());
+/// let mut wrong = forced.clone();
+/// wrong[0] = ("3".to_string(), "2".to_string());
+/// assert!(check_certificate(a, b, sol, &wrong, t, true).is_err());
+/// let sol = ("1 2 3 4 6 5", "4 5 1 2 3 6");
+/// assert!(check_certificate(a, b, sol, &forced, t, true).is_err());
+/// ```
+pub fn check_certificate(
+    a: &str,
+    b: &str,
+    sol: (&str, &str),
+    forced: &[(String, String)],
+    t: f64,
+    is_minimising: bool,
+) -> Result<()> {
+    let (rank_a, rank_b, sol_a, sol_b) = parse_arbitration(a, b, sol)?;
+    let n = sol_a.len();
+    let groups_a = extension_of(&rank_a, &sol_a)?;
+    let groups_b = extension_of(&rank_b, &sol_b)?;
+
+    let items = sol_a.ensure_defined()?.concat();
+    let pos_a = items
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (*e, i))
+        .collect::<BTreeMap<Element, usize>>();
+    let pos_b = sol_b
+        .ensure_defined()?
+        .concat()
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| (e, i))
+        .collect::<BTreeMap<Element, usize>>();
+    // the items of `sol` in a are in the order of its names
+    let names = sol.0.split_whitespace().zip(items.iter().copied());
+    let elements = names.collect::<BTreeMap<&str, Element>>();
+    let element = |name: &str| {
+        elements
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("{name} isn't an item of the input"))
+    };
+
+    // every pair of the certificate goes against the bound, and is fixed in both
+    let mut listed = BTreeSet::new();
+    for (x, y) in forced {
+        let (ex, ey) = (element(x)?, element(y)?);
+        ensure!(
+            pos_a[&ex] < pos_a[&ey],
+            "({x},{y}) isn't in the order of the arbitration"
+        );
+        let concordant = pos_b[&ex] < pos_b[&ey];
+        ensure!(
+            concordant == is_minimising,
+            "({x},{y}) doesn't go against the bound"
+        );
+        if groups_a[&ex] == groups_a[&ey] || groups_b[&ex] == groups_b[&ey] {
+            bail!("({x},{y}) goes against the bound, but isn't forced");
+        }
+        ensure!(
+            listed.insert((ex, ey)),
+            "({x},{y}) is in the certificate twice"
+        );
+    }
+
+    // and no other pair does
+    let mut against = 0;
+    for (i, x) in items.iter().enumerate() {
+        for y in &items[i + 1..] {
+            let concordant = pos_b[x] < pos_b[y];
+            if concordant != is_minimising {
+                continue;
+            }
+            against += 1;
+        }
+    }
+    ensure!(
+        against == listed.len(),
+        "{against} pairs go against the bound, the certificate has {}",
+        listed.len()
+    );
+
+    // all the other pairs are for the bound
+    let total = (n * n.saturating_sub(1) / 2) as f64;
+    let expected = if is_minimising {
+        (2.0 * against as f64 - total) / total
+    } else {
+        (total - 2.0 * against as f64) / total
+    };
+    ensure!(
+        (expected - t).abs() <= PRECISION,
+        "the certificate gives tau {expected}, not {t}"
+    );
+
+    Ok(())
+}
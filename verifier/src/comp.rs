@@ -1,67 +1,93 @@
-//! compare the outputs of two algorithms
-
+//! compare the outputs of two or more algorithms
+//!
+//! every case is run by every solver, and its row says which of them agree (see
+//! [`verifier::compare`]), whose arbitrations don't achieve the bound they were
+//! given for, and what each of them found. at the end there's, per solver, how
+//! often it deviated from the rest or gave an invalid arbitration, how often
+//! each two of them disagree, and how long their runs took.
 use std::collections::BTreeMap;
-use std::ops::Sub;
 use std::path::PathBuf;
+use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use anyhow::Result;
 use anyhow::anyhow;
-use anyhow::bail;
+use anyhow::ensure;
 use clap::Parser;
 use clap_derive::Parser;
 use csv::Writer;
 use indicatif::ParallelProgressIterator;
 use lib::AlgoOut;
 use lib::CHUNK_SIZE;
+use lib::Limits;
 use lib::RankingsCsvRow;
-use lib::def::Ranking;
-use lib::def::partial_from_string;
+use lib::RunStatus;
+use lib::Usage;
+use lib::canon::dedup_rows;
 use lib::display_cases;
+use lib::expr::expression_weight;
 use lib::parse_row;
 use lib::progress_bar;
 use lib::read_glob_csv;
 use lib::run_solver_on;
+use lib::shard::Shard;
+use lib::shard::output_path;
+use lib::stream::count_rows;
+use lib::stream::pipeline;
+use lib::stream::stream_rows;
+use lib::weights::by_name;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
+use verifier::compare::Agreement;
+use verifier::compare::Timing;
+use verifier::compare::invalid_arbitration;
 use verifier::verify::parse_algo_sol;
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct Cli {
-    pub algo_one: PathBuf,
-    pub algo_two: PathBuf,
-    pub output: PathBuf,
-    pub data: String,
+    /// the solvers to compare (at least two), then the output and the data
+    #[arg(num_args = 4.., required = true, value_names = ["SOLVERS", "OUTPUT", "DATA"])]
+    pub paths: Vec<String>,
+    #[command(flatten)]
+    pub limits: Limits,
+    /// only run slice i of n (from 1) of the cases, see `lib::shard`
+    #[arg(long)]
+    pub shard: Option<Shard>,
+    /// read the input a chunk at a time, in its order, instead of all at once
+    /// (see `lib::stream`). can't be sharded
+    #[arg(long, conflicts_with = "shard")]
+    pub stream: bool,
+    /// the weight the solvers use, for checking their arbitrations: a weight
+    /// name, or an expression in xa, xb, ya, yb
+    #[arg(long, default_value = "unweighted")]
+    pub weight: String,
 }
 
-enum CompResult {
-    LeftMissing(AlgoOut, RankingsCsvRow),
-    RightMissing(AlgoOut, RankingsCsvRow),
-    BothMissing(RankingsCsvRow),
-    TauNotEqual(AlgoOut, AlgoOut, RankingsCsvRow),
-    SolNotEqual(AlgoOut, AlgoOut, RankingsCsvRow),
-    Equal(AlgoOut, AlgoOut, RankingsCsvRow),
+/// what the solvers did on one case
+struct Compared {
+    runs: Vec<(RunStatus, Duration, Usage)>,
+    outs: Vec<Option<AlgoOut>>,
+    agreement: Agreement,
+    /// what's wrong with the arbitrations of each solver, if anything
+    invalid: Vec<Option<String>>,
 }
 
-#[derive(Debug, Clone, serde_derive::Serialize)]
-struct CompOutRow {
-    pub err_type: String,
-    pub a: String,
-    pub b: String,
-    pub dtmin: f64,
-    pub ltmin: f64,
-    pub rtmin: f64,
-    pub dtmax: f64,
-    pub ltmax: f64,
-    pub rtmax: f64,
-    pub lpmin: String,
-    pub rpmin: String,
-    pub lpmax: String,
-    pub rpmax: String,
+/// what happened over all the cases, per solver where it's by solver
+struct Tally {
+    err_types: BTreeMap<&'static str, usize>,
+    deviating: Vec<usize>,
+    invalid: Vec<usize>,
+    missing: Vec<usize>,
+    timeouts: Vec<usize>,
+    exceeded: Vec<usize>,
+    /// `[i][j]`: cases where both finished and disagree
+    disagree: Vec<Vec<usize>>,
+    /// of the runs that finished
+    times: Vec<Vec<Duration>>,
 }
 
 fn main() -> Result<()> {
@@ -69,283 +95,349 @@ fn main() -> Result<()> {
 
     let start = Instant::now();
 
-    if !args.algo_one.is_file() {
-        bail!("algo one not found: {}", args.algo_one.display());
-    }
-    if !args.algo_two.is_file() {
-        bail!("algo two not found: {}", args.algo_two.display());
+    let (solvers, rest) = args.paths.split_at(args.paths.len() - 2);
+    let solvers = solvers.iter().map(PathBuf::from).collect::<Vec<_>>();
+    let (output, data) = (PathBuf::from(&rest[0]), &rest[1]);
+    for solver in &solvers {
+        ensure!(solver.is_file(), "solver not found: {}", solver.display());
     }
+    let names = solver_names(&solvers);
+    let w = by_name(&args.weight).or_else(|_| expression_weight(&args.weight))?;
 
-    let rows = read_glob_csv(&args.data, vec![])?;
-
-    let mut inputs = rows
-        .par_iter()
-        .map(parse_row)
-        .collect::<Result<Vec<RankingsCsvRow>>>()?;
-    inputs.dedup_by(|r1, r2| {
-        let mut map = BTreeMap::new();
-        let p1 = partial_from_string(&r1.a, &mut map).unwrap();
-        let p2 = partial_from_string(&r1.b, &mut map).unwrap();
-        let mut map = BTreeMap::new();
-        let p3 = partial_from_string(&r2.a, &mut map).unwrap();
-        let p4 = partial_from_string(&r2.b, &mut map).unwrap();
-        p1.rank_eq(&p3) && p2.rank_eq(&p4)
-    });
-    inputs.sort_unstable_by_key(|x| x.a.len() + x.b.len());
-
-    let num_tests = inputs.len();
-    println!(
-        "parsed {} into {num_tests} lines of input in {}s",
-        rows.len(),
-        start.elapsed().as_secs_f32()
-    );
-
-    let pb = progress_bar(num_tests as u64)?;
-
-    let log_file = if args.output.is_dir() {
+    let log_file = if output.is_dir() {
+        // the shards would all make up a different name
+        ensure!(
+            args.shard.is_none(),
+            "give the shards an output file to share, not a directory"
+        );
         // create a new logfile with the current timestamp as name
         let new_file_name = format!(
             "{}_comp_log.csv",
             (SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / 30) & 0xfff
         );
-        let p = args.output.join(new_file_name);
+        let p = output.join(new_file_name);
         std::fs::File::create_new(&p)
             .map_err(|e| anyhow!("error creating file {}: {e}", p.display()))?;
         println!("creating {}", p.display());
         Ok::<PathBuf, anyhow::Error>(p)
     } else {
         // whatever the user pointed to, i dont care
-        Ok(args.output)
+        if let Some(shard) = args.shard {
+            shard.start(&output)?;
+        }
+        Ok(output_path(&output, args.shard))
     }?;
     let mut writer = Writer::from_path(&log_file)
         .map_err(|e| anyhow!("couldn't open output file ({}): {e}", log_file.display()))?;
+    writer.write_record(header(&names))?;
 
-    let mut equals = 0;
-    let mut l_missing = 0;
-    let mut r_missing = 0;
-    let mut both_missing = 0;
-    let mut tau_not_eq = 0;
-    let mut sol_not_eq = 0;
+    let n = solvers.len();
+    let mut tally = Tally {
+        err_types: BTreeMap::new(),
+        deviating: vec![0; n],
+        invalid: vec![0; n],
+        missing: vec![0; n],
+        timeouts: vec![0; n],
+        exceeded: vec![0; n],
+        disagree: vec![vec![0; n]; n],
+        times: vec![Vec::new(); n],
+    };
 
-    println!("running {num_tests} tests...");
+    let pb = progress_bar(0)?;
+    // cases some solver went over the limits on
+    let mut over_limits = 0;
+    let mut solve = |group: &[RankingsCsvRow]| {
+        let runs = group
+            .par_iter()
+            .map(|i| {
+                solvers
+                    .iter()
+                    .map(|s| run_solver_on(s, &i, args.limits))
+                    .collect::<Result<Vec<_>>>()
+                    .map(|r| (r, i))
+            })
+            .progress_with(pb.clone())
+            .collect::<Result<Vec<_>>>()?;
 
-    inputs
-        .chunks(CHUNK_SIZE)
-        .try_for_each(|group| {
-            let comp_results = group
-                .par_iter()
-                .map(|i| {
-                    run_solver_on(&args.algo_one, &i)
-                        .map(|a| run_solver_on(&args.algo_two, &i).map(|b| (a, b, i)))
-                })
-                .progress_with(pb.clone())
-                .collect::<Result<Result<Vec<_>>>>()??
-                .par_iter()
-                .map(|(a, b, i)| {
-                    parse_algo_sol(a.0.to_string()).map(|aa| {
-                        parse_algo_sol(b.0.to_string()).map(|bb| (aa, bb, (*i).clone()))
+        let compared = runs
+            .into_par_iter()
+            .map(|(runs, i)| {
+                let outs = runs
+                    .iter()
+                    .map(|(status, _, _)| parse_run(status))
+                    .collect::<Result<Vec<_>>>()?;
+                let invalid = outs
+                    .iter()
+                    .map(|o| {
+                        o.as_ref()
+                            .and_then(|o| invalid_arbitration(&i.a, &i.b, o, w))
                     })
-                })
-                .collect::<Result<Result<Vec<_>>>>()??
-                .into_par_iter()
-                .map(compare_results)
-                .collect::<Result<Vec<_>>>()?;
+                    .collect();
+                let agreement = Agreement::of(&outs);
+                Ok((
+                    Compared {
+                        runs,
+                        outs,
+                        agreement,
+                        invalid,
+                    },
+                    i,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-            comp_results
-                .into_iter()
-                .flat_map(|cr| match cr {
-                    CompResult::Equal(l, r, c) => {
-                        equals += 1;
-                        Some(CompOutRow {
-                            err_type: "none".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmin: 0.0,
-                            ltmin: l.tmin.unwrap_or(f64::NAN),
-                            rtmin: r.tmin.unwrap_or(f64::NAN),
-                            dtmax: 0.0,
-                            ltmax: l.tmax.unwrap_or(f64::NAN),
-                            rtmax: r.tmax.unwrap_or(f64::NAN),
-                            lpmin: display_cases(&l.minp),
-                            rpmin: display_cases(&r.minp),
-                            lpmax: display_cases(&l.maxp),
-                            rpmax: display_cases(&r.maxp),
-                        })
-                    }
-                    CompResult::LeftMissing(r, c) => {
-                        l_missing += 1;
-                        Some(CompOutRow {
-                            err_type: "left missing".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmin: 0.0,
-                            ltmin: 0.0,
-                            rtmin: r.tmin.unwrap_or(f64::NAN),
-                            dtmax: 0.0,
-                            ltmax: 0.0,
-                            rtmax: r.tmax.unwrap_or(f64::NAN),
-                            lpmin: "none".into(),
-                            rpmin: display_cases(&r.minp),
-                            lpmax: "none".into(),
-                            rpmax: display_cases(&r.maxp),
-                        })
-                    }
-                    CompResult::RightMissing(l, c) => {
-                        r_missing += 1;
-                        Some(CompOutRow {
-                            err_type: "right missing".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmin: 0.0,
-                            rtmin: 0.0,
-                            ltmin: l.tmin.unwrap_or(f64::NAN),
-                            dtmax: 0.0,
-                            rtmax: 0.0,
-                            ltmax: l.tmax.unwrap_or(f64::NAN),
-                            rpmin: "none".into(),
-                            lpmin: display_cases(&l.minp),
-                            rpmax: "none".into(),
-                            lpmax: display_cases(&l.maxp),
-                        })
-                    }
-                    CompResult::TauNotEqual(l, r, c) => {
-                        tau_not_eq += 1;
-                        Some(CompOutRow {
-                            err_type: "bounds not equal".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmin: l
-                                .tmin
-                                .unwrap_or(f64::NAN)
-                                .sub(r.tmin.unwrap_or(f64::NAN))
-                                .abs(),
-                            ltmin: l.tmin.unwrap_or(f64::NAN),
-                            rtmin: r.tmin.unwrap_or(f64::NAN),
-                            dtmax: l
-                                .tmax
-                                .unwrap_or(f64::NAN)
-                                .sub(r.tmax.unwrap_or(f64::NAN))
-                                .abs(),
-                            ltmax: l.tmax.unwrap_or(f64::NAN),
-                            rtmax: r.tmax.unwrap_or(f64::NAN),
-                            lpmin: display_cases(&l.minp),
-                            rpmin: display_cases(&r.minp),
-                            lpmax: display_cases(&l.maxp),
-                            rpmax: display_cases(&r.maxp),
-                        })
-                    }
-                    CompResult::SolNotEqual(l, r, c) => {
-                        sol_not_eq += 1;
-                        Some(CompOutRow {
-                            err_type: "rankings not equal".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmin: l
-                                .tmin
-                                .unwrap_or(f64::NAN)
-                                .sub(r.tmin.unwrap_or(f64::NAN))
-                                .abs(),
-                            ltmin: l.tmin.unwrap_or(f64::NAN),
-                            rtmin: r.tmin.unwrap_or(f64::NAN),
-                            dtmax: l
-                                .tmax
-                                .unwrap_or(f64::NAN)
-                                .sub(r.tmax.unwrap_or(f64::NAN))
-                                .abs(),
-                            ltmax: l.tmax.unwrap_or(f64::NAN),
-                            rtmax: r.tmax.unwrap_or(f64::NAN),
-                            lpmin: display_cases(&l.minp),
-                            rpmin: display_cases(&r.minp),
-                            lpmax: display_cases(&l.maxp),
-                            rpmax: display_cases(&r.maxp),
-                        })
+        for (compared, case) in compared {
+            let agreement = &compared.agreement;
+            over_limits += usize::from(
+                compared
+                    .runs
+                    .iter()
+                    .any(|(status, _, _)| !matches!(status, RunStatus::Done(_))),
+            );
+            for (s, (status, time, _)) in compared.runs.iter().enumerate() {
+                match status {
+                    RunStatus::Timeout => tally.timeouts[s] += 1,
+                    RunStatus::ResourceExceeded => tally.exceeded[s] += 1,
+                    RunStatus::Done(_) => tally.times[s].push(*time),
+                }
+            }
+            for &s in &agreement.missing {
+                tally.missing[s] += 1;
+            }
+            for s in agreement.deviating() {
+                tally.deviating[s] += 1;
+            }
+            for (s, invalid) in compared.invalid.iter().enumerate() {
+                tally.invalid[s] += usize::from(invalid.is_some());
+            }
+            for i in 0..n {
+                for j in 0..n {
+                    if agreement.disagree(i, j) {
+                        tally.disagree[i][j] += 1;
                     }
-                    CompResult::BothMissing(c) => {
-                        both_missing += 1;
-                        Some(CompOutRow {
-                            err_type: "missing both".into(),
-                            a: c.a,
-                            b: c.b,
-                            dtmax: 0.0,
-                            dtmin: 0.0,
-                            ltmin: 0.0,
-                            rtmin: 0.0,
-                            ltmax: 0.0,
-                            rtmax: 0.0,
-                            lpmin: "none".into(),
-                            rpmin: "none".into(),
-                            lpmax: "none".into(),
-                            rpmax: "none".into(),
-                        })
-                    }
-                })
-                .try_for_each(|o| writer.serialize(o))
-                .map_err(|e| anyhow!("writer err: {e:?}"))
-                .and_then(|_| writer.flush().map_err(|e| anyhow!("couldn't flush: {e:?}")))
-        })
-        .unwrap();
+                }
+            }
+            let err_type = err_type(&compared);
+            *tally.err_types.entry(err_type).or_default() += 1;
 
-    let not_eqs = tau_not_eq + sol_not_eq;
+            writer.write_record(record(err_type, case, &names, &compared))?;
+        }
+        writer.flush().map_err(|e| anyhow!("couldn't flush: {e:?}"))
+    };
 
-    println!("{num_tests} done in {}s", start.elapsed().as_secs_f32());
-    if equals > 0 {
-        println!("success: {equals}");
-    }
-    if not_eqs > 0 {
-        println!("failures: {not_eqs}");
-        println!("| sol ≠: {sol_not_eq}");
-        println!("| tau ≠: {tau_not_eq}");
-    }
-    if l_missing > 0 {
-        println!("left empty: {l_missing}");
-    }
-    if r_missing > 0 {
-        println!("right empty: {r_missing}");
+    let num_tests = if args.stream {
+        let num_tests = count_rows(data, &[])?;
+        println!("running {num_tests} tests...");
+        pb.set_length(num_tests as u64);
+        let chunks = stream_rows(data, &[], 0..num_tests, CHUNK_SIZE)?;
+        pipeline(chunks, 2, |group| solve(&dedup_rows(group)?))?;
+        num_tests
+    } else {
+        let rows = read_glob_csv(data, vec![])?;
+
+        let inputs = rows
+            .par_iter()
+            .map(parse_row)
+            .collect::<Result<Vec<RankingsCsvRow>>>()?;
+        // the same input under other names only needs solving once
+        let mut inputs = dedup_rows(inputs)?;
+        inputs.sort_unstable_by_key(|x| x.a.len() + x.b.len());
+        if let Some(shard) = args.shard {
+            shard.select(&mut inputs);
+        }
+
+        let num_tests = inputs.len();
+        println!(
+            "parsed {} into {num_tests} lines of input in {}s",
+            rows.len(),
+            start.elapsed().as_secs_f32()
+        );
+        println!("running {num_tests} tests...");
+        pb.set_length(num_tests as u64);
+        inputs.chunks(CHUNK_SIZE).try_for_each(&mut solve)?;
+        num_tests
+    };
+
+    if let Some(shard) = args.shard {
+        shard.finish(&output, num_tests, over_limits)?;
     }
-    if both_missing > 0 {
-        println!("both empty: {both_missing}");
+
+    println!("{num_tests} done in {}s", start.elapsed().as_secs_f32());
+    for (err_type, count) in &tally.err_types {
+        println!("{err_type}: {count}");
     }
+    print_by_solver(&names, &tally);
 
     Ok(())
 }
 
-fn compare_results(
-    out: (Option<AlgoOut>, Option<AlgoOut>, RankingsCsvRow),
-) -> Result<CompResult> {
-    let case = out.2;
-    let (left, right) = match (out.0, out.1) {
-        (None, None) => return Ok(CompResult::BothMissing(case)),
-        (Some(l), None) => return Ok(CompResult::RightMissing(l, case)),
-        (None, Some(r)) => return Ok(CompResult::LeftMissing(r, case)),
-        (Some(l), Some(r)) => (l, r),
-    };
+/// what the solvers are called in the output: their file names, numbered if
+/// two have the same one
+fn solver_names(solvers: &[PathBuf]) -> Vec<String> {
+    let names = solvers
+        .iter()
+        .map(|s| {
+            s.file_name()
+                .map_or("solver".to_string(), |f| f.to_string_lossy().to_string())
+        })
+        .collect::<Vec<_>>();
+    names
+        .iter()
+        .enumerate()
+        .map(
+            |(i, name)| match names.iter().filter(|n| *n == name).count() {
+                1 => name.clone(),
+                _ => format!("{name}#{}", i + 1),
+            },
+        )
+        .collect()
+}
 
-    if left.eq(&right) {
-        Ok(CompResult::Equal(left, right, case))
-    } else {
-        // check that neither solution is a superset of the other
-        if either_set_rel(&left.minp, &right.minp)
-            && either_set_rel(&left.maxp, &right.maxp)
-        {
-            // the algorithms agree on the solutions,
-            // so it's just the tau calculation that's wrong
-            Ok(CompResult::TauNotEqual(left, right, case))
-        } else {
-            Ok(CompResult::SolNotEqual(left, right, case))
+fn header(names: &[String]) -> Vec<String> {
+    let mut header = [
+        "err_type",
+        "a",
+        "b",
+        "clusters",
+        "deviating",
+        "invalid",
+        "missing",
+        "dtmin",
+        "dtmax",
+    ]
+    .map(String::from)
+    .to_vec();
+    for name in names {
+        for column in ["tmin", "tmax", "pmin", "pmax", "time", "check"] {
+            header.push(format!("{column}_{name}"));
         }
     }
+    header
+}
+
+/// what went wrong on a case, if anything. a solver whose arbitrations
+/// aren't what it says is a real disagreement on the solutions, while solvers
+/// that back their bounds up but still differ found arbitrations that aren't
+/// as extreme as the others.
+fn err_type(compared: &Compared) -> &'static str {
+    let invalid = compared.invalid.iter().any(Option::is_some);
+    match compared.agreement.clusters.as_slice() {
+        [] => "all missing",
+        _ if invalid => "solutions not equal",
+        [_] if compared.agreement.missing.is_empty() => "none",
+        [_] => "missing",
+        _ => "bounds not equal",
+    }
 }
 
-fn either_set_rel(a: &[(String, String)], b: &[(String, String)]) -> bool {
-    superset_rel(a, b) || superset_rel(b, a)
+fn record(
+    err_type: &str,
+    case: &RankingsCsvRow,
+    names: &[String],
+    compared: &Compared,
+) -> Vec<String> {
+    let Compared {
+        runs,
+        outs,
+        agreement,
+        invalid,
+    } = compared;
+    let listed = |solvers: &[usize]| {
+        solvers
+            .iter()
+            .map(|s| names[*s].as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let spread = |bound: fn(&AlgoOut) -> Option<f64>| {
+        let values = outs.iter().flatten().filter_map(bound);
+        let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+        let min = values.fold(f64::INFINITY, f64::min);
+        if max >= min { max - min } else { 0.0 }
+    };
+
+    let mut record = vec![
+        err_type.to_string(),
+        case.a.clone(),
+        case.b.clone(),
+        agreement
+            .clusters
+            .iter()
+            .map(|c| listed(c))
+            .collect::<Vec<_>>()
+            .join("|"),
+        listed(&agreement.deviating()),
+        listed(
+            &(0..names.len())
+                .filter(|s| invalid[*s].is_some())
+                .collect::<Vec<_>>(),
+        ),
+        listed(&agreement.missing),
+        format!("{:?}", spread(|o| o.tmin)),
+        format!("{:?}", spread(|o| o.tmax)),
+    ];
+    for ((out, (_, time, _)), invalid) in outs.iter().zip(runs).zip(invalid) {
+        match out {
+            Some(o) => record.extend([
+                format!("{:?}", o.tmin.unwrap_or(f64::NAN)),
+                format!("{:?}", o.tmax.unwrap_or(f64::NAN)),
+                display_cases(&o.minp),
+                display_cases(&o.maxp),
+            ]),
+            None => record.extend(["NaN", "NaN", "none", "none"].map(String::from)),
+        }
+        record.push(format!("{:?}", time.as_secs_f32()));
+        record.push(invalid.clone().unwrap_or_default());
+    }
+    record
 }
 
-/// is the left slice a superset of the right?
-fn superset_rel(left: &[(String, String)], right: &[(String, String)]) -> bool {
-    for el in right {
-        if !left.contains(el) {
-            return false;
+fn print_by_solver(names: &[String], tally: &Tally) {
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(8);
+    for (s, name) in names.iter().enumerate() {
+        println!(
+            "{name:width$}  deviating: {}, invalid arbitrations: {}, missing: {} (timed out: {}, out of memory: {})",
+            tally.deviating[s],
+            tally.invalid[s],
+            tally.missing[s],
+            tally.timeouts[s],
+            tally.exceeded[s]
+        );
+    }
+
+    println!("disagreements, where both finished:");
+    print!("{:width$}", "");
+    for name in names {
+        print!("  {name:>width$}");
+    }
+    println!();
+    for (i, name) in names.iter().enumerate() {
+        print!("{name:width$}");
+        for j in 0..names.len() {
+            match i == j {
+                true => print!("  {:>width$}", "-"),
+                false => print!("  {:>width$}", tally.disagree[i][j]),
+            }
         }
+        println!();
+    }
+
+    println!("times of the runs that finished, in seconds:");
+    for (name, times) in names.iter().zip(&tally.times) {
+        match Timing::of(times.clone()) {
+            Some(t) => println!(
+                "{name:width$}  {} runs, mean {:.4}, median {:.4}, p90 {:.4}, max {:.4}",
+                t.runs, t.mean, t.median, t.p90, t.max
+            ),
+            None => println!("{name:width$}  no runs"),
+        }
+    }
+}
+
+/// the parsed output of a run, if it finished (and wasn't skipped)
+fn parse_run(status: &RunStatus) -> Result<Option<AlgoOut>> {
+    match status {
+        RunStatus::Done(output) => parse_algo_sol(output.to_string()),
+        RunStatus::Timeout | RunStatus::ResourceExceeded => Ok(None),
     }
-    true
 }
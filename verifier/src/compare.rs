@@ -0,0 +1,169 @@
+//! comparing the outputs of several solvers on the same case
+//!
+//! the solvers that finished are put in clusters of ones that agree (see
+//! `PartialEq for AlgoOut`), compared with the first of each cluster. the
+//! solvers outside the largest cluster are the ones that deviate. two solvers
+//! can find different optimal arbitrations, so those aren't compared: instead
+//! each solver's arbitrations have to be valid, see [`invalid_arbitration`].
+use std::time::Duration;
+
+use lib::AlgoOut;
+use lib::weights::WeightFn;
+
+use crate::consistency::check_bound;
+
+/// how the solvers of one case agree, by their index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Agreement {
+    /// the solvers that agree, in the order they were given. the first
+    /// cluster has the first solver that finished.
+    pub clusters: Vec<Vec<usize>>,
+    /// the ones that didn't finish, were skipped, or found neither bound
+    pub missing: Vec<usize>,
+}
+
+impl Agreement {
+    /// ```
+    /// # use lib::AlgoOut;
+    /// # use verifier::compare::Agreement;
+    /// let out = |tmin| Some(AlgoOut { tmin: Some(tmin), tmax: Some(1.0), ..Default::default() });
+    /// let nothing = Some(AlgoOut::default());
+    /// let agreement = Agreement::of(&[out(0.5), None, out(0.2), out(0.5), nothing]);
+    /// assert_eq!(agreement.clusters, vec![vec![0, 3], vec![2]]);
+    /// assert_eq!(agreement.missing, vec![1, 4]);
+    /// assert_eq!(agreement.deviating(), vec![2]);
+    /// ```
+    pub fn of(outs: &[Option<AlgoOut>]) -> Agreement {
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        let mut missing = Vec::new();
+        for (i, out) in outs.iter().enumerate() {
+            let Some(out) = out
+                .as_ref()
+                .filter(|o| o.tmin.is_some() || o.tmax.is_some())
+            else {
+                missing.push(i);
+                continue;
+            };
+            let same = clusters
+                .iter_mut()
+                .find(|c| outs[c[0]].as_ref().is_some_and(|first| first == out));
+            match same {
+                Some(cluster) => cluster.push(i),
+                None => clusters.push(vec![i]),
+            }
+        }
+        Agreement { clusters, missing }
+    }
+
+    /// the solvers that finished but aren't in the largest cluster (the first
+    /// of them, if some are as large)
+    pub fn deviating(&self) -> Vec<usize> {
+        let largest = self
+            .clusters
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, c)| (c.len(), std::cmp::Reverse(*i)))
+            .map(|(i, _)| i);
+        let mut deviating = self
+            .clusters
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != largest)
+            .flat_map(|(_, c)| c.iter().copied())
+            .collect::<Vec<_>>();
+        deviating.sort_unstable();
+        deviating
+    }
+
+    /// do solvers `i` and `j` both have an output, that isn't the same?
+    pub fn disagree(&self, i: usize, j: usize) -> bool {
+        let cluster = |s| self.clusters.iter().position(|c| c.contains(&s));
+        matches!((cluster(i), cluster(j)), (Some(ci), Some(cj)) if ci != cj)
+    }
+}
+
+/// what's wrong with the arbitrations a solver gave for its bounds of the input
+/// `a`, `b`, if anything: every one of them has to be a linear extension of the
+/// input, whose tau under `w` is the bound the solver reported.
+///
+/// ```
+/// # use lib::AlgoOut;
+/// # use lib::weights::unweighted;
+/// # use verifier::compare::invalid_arbitration;
+/// let out = AlgoOut {
+///     tmin: Some(-1.0),
+///     minp: vec![("2 1".to_string(), "1 2".to_string())],
+///     tmax: Some(1.0),
+///     maxp: vec![("1 2".to_string(), "1 2".to_string())],
+///     ..Default::default()
+/// };
+/// assert_eq!(invalid_arbitration("(1 2)", "(1 2)", &out, unweighted), None);
+/// let out = AlgoOut { tmin: Some(0.0), ..out };
+/// assert_eq!(
+///     invalid_arbitration("(1 2)", "(1 2)", &out, unweighted).unwrap(),
+///     "tmin 0.0 is -1.0 for its arbitration"
+/// );
+/// let out = AlgoOut { tmin: Some(-1.0), minp: vec![], ..out };
+/// assert_eq!(
+///     invalid_arbitration("(1 2)", "(1 2)", &out, unweighted).unwrap(),
+///     "tmin: there's no arbitration for the bound"
+/// );
+/// ```
+pub fn invalid_arbitration(a: &str, b: &str, out: &AlgoOut, w: WeightFn) -> Option<String> {
+    for (name, t, sols) in [("tmin", out.tmin, &out.minp), ("tmax", out.tmax, &out.maxp)] {
+        let Some(t) = t else { continue };
+        match check_bound(a, b, t, sols, w) {
+            Ok(None) => {}
+            Ok(Some(recomputed)) => {
+                return Some(format!(
+                    "{name} {t:?} is {recomputed:?} for its arbitration"
+                ));
+            }
+            Err(e) => return Some(format!("{name}: {e}")),
+        }
+    }
+    None
+}
+
+/// the mean, median, 90th percentile and largest of some run times
+///
+/// ```
+/// # use std::time::Duration;
+/// # use verifier::compare::Timing;
+/// let times = (1..=10).map(Duration::from_secs).collect::<Vec<_>>();
+/// let t = Timing::of(times).unwrap();
+/// assert_eq!((t.runs, t.median, t.max), (10, 5.5, 10.0));
+/// assert!((t.p90 - 9.1).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub runs: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl Timing {
+    pub fn of(times: Vec<Duration>) -> Option<Timing> {
+        let mut secs = times.iter().map(Duration::as_secs_f64).collect::<Vec<_>>();
+        if secs.is_empty() {
+            return None;
+        }
+        secs.sort_unstable_by(f64::total_cmp);
+        Some(Timing {
+            runs: secs.len(),
+            mean: secs.iter().sum::<f64>() / secs.len() as f64,
+            median: quantile(&secs, 0.5),
+            p90: quantile(&secs, 0.9),
+            max: secs[secs.len() - 1],
+        })
+    }
+}
+
+/// the `q`-th quantile of sorted values, interpolated between the two nearest
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let at = q * (sorted.len() - 1) as f64;
+    let (lo, hi) = (at.floor() as usize, at.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (at - lo as f64)
+}
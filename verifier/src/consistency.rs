@@ -0,0 +1,157 @@
+//! checking solver outputs without a reference solution
+//!
+//! whatever the solver claims has to at least be consistent with the input: the
+//! arbitrations must be linear extensions of it, their tau must be the one
+//! reported, and the bounds must contain tau_a and tau_b of the input. none of
+//! this needs the brute force, so it works for inputs of any size.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use anyhow::bail;
+use anyhow::ensure;
+use lib::AlgoOut;
+use lib::PRECISION;
+use lib::def::*;
+use lib::tau_w::TauVariants;
+use lib::tau_w::tau_auto;
+use lib::tau_w::tau_partial_auto;
+use lib::weights::WeightFn;
+
+use crate::FailType;
+
+/// the first way in which the output of the solver is inconsistent with the
+/// input `a`,`b`, if any.
+///
+/// ```
+/// # use lib::AlgoOut;
+/// # use lib::weights::unweighted;
+/// # use verifier::FailType;
+/// # use verifier::consistency::inconsistency;
+/// let out = AlgoOut {
+///     tmin: Some(-1.0),
+///     minp: vec![("2 1".to_string(), "1 2".to_string())],
+///     tmax: Some(1.0),
+///     maxp: vec![("1 2".to_string(), "1 2".to_string())],
+///     ..Default::default()
+/// };
+/// assert!(inconsistency("(1 2)", "(1 2)", &out, unweighted).is_none());
+/// let out = AlgoOut { tmax: Some(0.5), ..out };
+/// assert!(matches!(
+///     inconsistency("(1 2)", "(1 2)", &out, unweighted),
+///     Some(FailType::MaxRecomputed(..))
+/// ));
+/// ```
+pub fn inconsistency(
+    a: &str,
+    b: &str,
+    algo_sol: &AlgoOut,
+    w: WeightFn,
+) -> Option<FailType> {
+    if let Some(t) = algo_sol.tmin {
+        match check_bound(a, b, t, &algo_sol.minp, w) {
+            Ok(Some(recomputed)) => return Some(FailType::MinRecomputed(t, recomputed)),
+            Err(e) => return Some(FailType::NotExtension(e.to_string())),
+            Ok(None) => {}
+        }
+    }
+    if let Some(t) = algo_sol.tmax {
+        match check_bound(a, b, t, &algo_sol.maxp, w) {
+            Ok(Some(recomputed)) => return Some(FailType::MaxRecomputed(t, recomputed)),
+            Err(e) => return Some(FailType::NotExtension(e.to_string())),
+            Ok(None) => {}
+        }
+    }
+
+    // tau_a and tau_b are the tau of some way of handling the ties, which
+    // can't be more extreme than the bounds
+    let mut map = BTreeMap::new();
+    let (Ok(rank_a), Ok(rank_b)) = (
+        partial_from_string(a, &mut map),
+        partial_from_string(b, &mut map),
+    ) else {
+        return Some(FailType::NotExtension("can't parse the input".to_string()));
+    };
+    let tmin = algo_sol.tmin.unwrap_or(f64::NEG_INFINITY);
+    let tmax = algo_sol.tmax.unwrap_or(f64::INFINITY);
+    for variant in [TauVariants::A, TauVariants::B] {
+        let t = match tau_partial_auto(&rank_a, &rank_b, w, variant) {
+            Ok(t) => t,
+            Err(e) => return Some(FailType::NotExtension(e.to_string())),
+        };
+        if t < tmin - PRECISION || t > tmax + PRECISION {
+            return Some(FailType::OutOfBounds(tmin, t, tmax));
+        }
+    }
+
+    None
+}
+
+/// the recomputed tau of the first arbitration of a bound whose reported tau
+/// `t` doesn't match, or an error if an arbitration isn't a linear extension or
+/// there is none.
+pub fn check_bound(
+    a: &str,
+    b: &str,
+    t: f64,
+    sols: &[(String, String)],
+    w: WeightFn,
+) -> Result<Option<f64>> {
+    ensure!(!sols.is_empty(), "there's no arbitration for the bound");
+    for (sa, sb) in sols {
+        let (rank_a, rank_b, sol_a, sol_b) = parse_arbitration(a, b, (sa, sb))?;
+        extension_of(&rank_a, &sol_a)?;
+        extension_of(&rank_b, &sol_b)?;
+        let recomputed = tau_auto(&sol_a, &sol_b, w)?;
+        if (recomputed - t).abs() > PRECISION {
+            return Ok(Some(recomputed));
+        }
+    }
+    Ok(None)
+}
+
+/// parse the input `a`,`b` and an arbitration `sol` of it, which must use the
+/// same items.
+pub fn parse_arbitration(
+    a: &str,
+    b: &str,
+    sol: (&str, &str),
+) -> Result<(PartialOrder, PartialOrder, StrictOrder, StrictOrder)> {
+    let mut map = BTreeMap::new();
+    let rank_a = partial_from_string(a, &mut map)?;
+    let rank_b = partial_from_string(b, &mut map)?;
+    let (n, _item_set) = rank_a.ensure_conjoint(&rank_b)?;
+    let sol_a = strict_from_partial(&partial_from_string(sol.0, &mut map)?)?;
+    let sol_b = strict_from_partial(&partial_from_string(sol.1, &mut map)?)?;
+    ensure!(map.len() == n, "the arbitration has items not in the input");
+    Ok((rank_a, rank_b, sol_a, sol_b))
+}
+
+/// the tie group of every element of `rank`, if `sol` is a linear extension of
+/// it.
+pub fn extension_of(
+    rank: &PartialOrder,
+    sol: &StrictOrder,
+) -> Result<BTreeMap<Element, usize>> {
+    ensure!(
+        rank.set_size() == sol.len(),
+        "the arbitration has {} items, not {}",
+        sol.len(),
+        rank.set_size()
+    );
+    let mut groups = BTreeMap::new();
+    let mut idx = 0;
+    for (g, tg) in rank.iter().enumerate() {
+        for e in &sol[idx..idx + tg.len()] {
+            match e {
+                Some(e) if tg.contains(e) => groups.insert(*e, g),
+                _ => bail!("the arbitration isn't a linear extension of the input"),
+            };
+        }
+        idx += tg.len();
+    }
+    ensure!(
+        groups.len() == sol.len(),
+        "the arbitration has repeated items"
+    );
+    Ok(groups)
+}
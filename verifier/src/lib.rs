@@ -1,7 +1,11 @@
 use lib::AlgoOut;
 use lib::Case;
 
+pub mod certificate;
+pub mod compare;
+pub mod consistency;
 pub mod parsing;
+pub mod shrink;
 pub mod verify;
 
 #[derive(Debug, Clone, serde_derive::Deserialize)]
@@ -12,6 +16,11 @@ pub struct CsvRow {
     pub tmax: f64,
     pub min_sols: String,
     pub max_sols: String,
+    /// how many optimal arbitrations there are, if the reference counted them
+    #[serde(default)]
+    pub nmin: Option<u128>,
+    #[serde(default)]
+    pub nmax: Option<u128>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +31,15 @@ pub struct TestCase {
     pub tmax: f64,
     pub min_sol_pairs: Vec<(String, String)>,
     pub max_sol_pairs: Vec<(String, String)>,
+    /// how many optimal arbitrations there are, if the reference counted them.
+    /// the pairs above can be fewer than that, if there were too many to list.
+    pub nmin: Option<u128>,
+    pub nmax: Option<u128>,
+    /// have the solver list (up to) this many optimal arbitrations and count
+    /// all of them (its `--all`)
+    pub all: Option<usize>,
+    /// have the solver certify its bounds (its `--certify`)
+    pub certify: bool,
 }
 
 /// failure types. value is (actual, expected)
@@ -31,6 +49,19 @@ pub enum FailType {
     Tmax(f64, f64),
     MinP,
     MaxP,
+    /// the number of optimal arbitrations, (counted, expected)
+    MinCount(u128, u128),
+    MaxCount(u128, u128),
+    /// the certificate of optimality doesn't hold, and why
+    MinCert(String),
+    MaxCert(String),
+    /// an arbitration isn't a linear extension of the input, and why
+    NotExtension(String),
+    /// the reported tau isn't the tau of the arbitration, (reported, recomputed)
+    MinRecomputed(f64, f64),
+    MaxRecomputed(f64, f64),
+    /// tau_a or tau_b of the input is outside the bounds, (tmin, tau, tmax)
+    OutOfBounds(f64, f64, f64),
 }
 
 type FailInfo = (TestCase, AlgoOut);
@@ -41,12 +72,47 @@ pub enum TestResult {
     Complete,
     Pass,
     Skipped,
+    /// the solver went over the time limit
+    Timeout,
+    /// the solver went over the memory limit
+    ResourceExceeded,
     Empty(TestCase),
     Fail(FailInfo, FailType),
 }
 
+impl TestCase {
+    /// an input without a reference solution
+    pub fn unsolved(a: &str, b: &str) -> Self {
+        Self {
+            a: a.to_string(),
+            b: b.to_string(),
+            tmin: f64::NAN,
+            tmax: f64::NAN,
+            min_sol_pairs: Vec::new(),
+            max_sol_pairs: Vec::new(),
+            nmin: None,
+            nmax: None,
+            all: None,
+            certify: false,
+        }
+    }
+}
+
 impl Case for TestCase {
+    /// ```
+    /// # use lib::Case;
+    /// # use verifier::TestCase;
+    /// let case = TestCase { all: Some(3), certify: true, ..TestCase::unsolved("(x y)", "y x") };
+    /// assert_eq!(case.algo_args(), ["(x y)", "y x", "--all", "3", "--certify"]);
+    /// ```
     fn algo_args(&self) -> Vec<String> {
-        vec![self.a.to_string(), self.b.to_string()]
+        let mut args = vec![self.a.to_string(), self.b.to_string()];
+        if let Some(n) = self.all {
+            args.extend(["--all".to_string(), n.to_string()]);
+        }
+        if self.certify {
+            args.push("--certify".to_string());
+        }
+        args
     }
 }
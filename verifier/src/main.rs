@@ -11,26 +11,40 @@
 //!     - pass the input
 //!     - assert the output is correct
 //!
+//! with `--all`, the solver also counts the optimal arbitrations, and the
+//! counts are compared to the reference's (its `nmin`,`nmax` columns, or the
+//! number of arbitrations it lists). with `--certify`, the solver also
+//! certifies its bounds, and the certificates are checked.
+//!
+//! with `--no-ref`, the data only needs the inputs (columns `a`,`b`), and the
+//! outputs are only checked for consistency with them (see
+//! [`verifier::consistency`]).
+//!
 //! exit:
 //! - code 0: all tests passed
 //! - anything else: something failed
 
 use std::path::PathBuf;
+use std::time::Duration;
 use std::time::Instant;
 
 use anyhow::Result;
+use anyhow::bail;
 use clap::Parser;
 use clap_derive::Parser;
 use indicatif::ParallelProgressIterator;
+use lib::Limits;
 use lib::progress_bar;
 use lib::read_glob_csv;
 use lib::run_solver_on;
+use lib::weights::by_name;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use verifier::TestCase;
 use verifier::TestResult;
 use verifier::parsing::parse_entry;
 use verifier::parsing::pretty_print;
+use verifier::verify::verify_consistency;
 use verifier::verify::verify_result;
 
 #[derive(Parser, Debug)]
@@ -38,6 +52,24 @@ use verifier::verify::verify_result;
 pub struct Cli {
     pub exec: PathBuf,
     pub data: String,
+    /// don't compare to reference solutions, only check that the outputs are
+    /// consistent with the inputs
+    #[arg(long)]
+    pub no_ref: bool,
+    /// the weight the solver uses, for recomputing tau without a reference
+    #[arg(long, default_value = "unweighted")]
+    pub weight: String,
+    /// run the solver with `--all N`, so it lists (up to) N optimal
+    /// arbitrations of each bound and counts all of them. the counts have to
+    /// be those of the reference.
+    #[arg(long, value_name = "N")]
+    pub all: Option<usize>,
+    /// run the solver with `--certify`, and check the certificates of
+    /// optimality it gives (see [`verifier::certificate`])
+    #[arg(long)]
+    pub certify: bool,
+    #[command(flatten)]
+    pub limits: Limits,
 }
 
 fn main() -> Result<()> {
@@ -45,7 +77,13 @@ fn main() -> Result<()> {
 
     let start = Instant::now();
 
-    let rows = read_glob_csv(&args.data, vec!["a", "b", "tmin", "tmax", "pmin", "pmax"])?;
+    let w = by_name(&args.weight)?;
+    let header = if args.no_ref {
+        vec![]
+    } else {
+        vec!["a", "b", "tmin", "tmax", "pmin", "pmax"]
+    };
+    let rows = read_glob_csv(&args.data, header)?;
 
     let num_tests = rows.len();
 
@@ -53,10 +91,27 @@ fn main() -> Result<()> {
 
     let inputs = rows
         .par_iter()
-        .map(parse_entry)
+        .map(|row| {
+            if args.no_ref {
+                // any csv will do, as long as it starts with the inputs
+                match (row.get(0), row.get(1)) {
+                    (Some(a), Some(b)) => Ok(TestCase::unsolved(a, b)),
+                    _ => bail!("improper row length ({}): {row:?}", row.len()),
+                }
+            } else {
+                parse_entry(row)
+            }
+        })
         .collect::<Result<Vec<TestCase>>>()?
         .into_iter()
-        .map(|i| (args.exec.clone(), i))
+        .map(|i| {
+            let i = TestCase {
+                all: args.all,
+                certify: args.certify,
+                ..i
+            };
+            (args.exec.clone(), i)
+        })
         .collect::<Vec<_>>();
 
     println!("parsed input in {}s", start.elapsed().as_secs_f32());
@@ -65,9 +120,21 @@ fn main() -> Result<()> {
 
     let results = inputs
         .par_iter()
-        .map(|(e, i)| run_solver_on(e, i.clone()).map(|out| (out, i.clone())))
+        .map(|(e, i)| {
+            run_solver_on(e, i.clone(), args.limits)
+                .map(|(status, dt, usage)| (((status, dt), i.clone()), usage))
+        })
         .progress_with(pb.clone())
-        .map(verify_result)
+        .map(|run| {
+            let (case, usage) = run?;
+            let case = Ok(case);
+            let (result, dt) = if args.no_ref {
+                verify_consistency(case, w)?
+            } else {
+                verify_result(case)?
+            };
+            Ok((result, dt, usage))
+        })
         .collect::<Result<Vec<_>>>()?;
 
     let successes = results
@@ -85,10 +152,24 @@ fn main() -> Result<()> {
         .filter(|x| matches!(x.0, TestResult::Skipped))
         .count();
 
+    let timeouts = results
+        .iter()
+        .filter(|x| matches!(x.0, TestResult::Timeout))
+        .count();
+
+    let exceeded = results
+        .iter()
+        .filter(|x| matches!(x.0, TestResult::ResourceExceeded))
+        .count();
+
     let failures = results
         .iter()
         .filter_map(|x| match &x.0 {
-            TestResult::Pass | TestResult::Skipped | TestResult::Complete => None,
+            TestResult::Pass
+            | TestResult::Skipped
+            | TestResult::Complete
+            | TestResult::Timeout
+            | TestResult::ResourceExceeded => None,
             TestResult::Fail(i, t) => Some((i, t)),
             TestResult::Empty(f) => {
                 println!(
@@ -104,9 +185,23 @@ fn main() -> Result<()> {
     println!("{completes}/{successes} solutions were complete.");
     println!("total time: {}s", start.elapsed().as_secs_f32());
 
+    if let Some(peak) = results.iter().map(|x| x.2.max_rss).max() {
+        let cpu = results.iter().map(|x| x.2.cpu).sum::<Duration>();
+        println!(
+            "peak rss: {peak} KiB, total cpu time: {}s",
+            cpu.as_secs_f32()
+        );
+    }
+
     if skips > 0 {
         println!("{skips} run(s) skipped");
     }
+    if timeouts > 0 {
+        println!("{timeouts} run(s) timed out");
+    }
+    if exceeded > 0 {
+        println!("{exceeded} run(s) ran out of memory");
+    }
 
     match failures.len() {
         0 => {}
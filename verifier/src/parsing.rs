@@ -8,7 +8,8 @@ use crate::TestCase;
 
 /// get the input & solution from csv row
 pub fn parse_entry(row: &StringRecord) -> Result<TestCase> {
-    if row.len() != 6 {
+    // the counts of optimal arbitrations are optional
+    if row.len() != 6 && row.len() != 8 {
         bail!("improper row length ({}): {row:?}", row.len());
     }
 
@@ -38,6 +39,10 @@ pub fn parse_entry(row: &StringRecord) -> Result<TestCase> {
         tmax: parsed_row.tmax,
         min_sol_pairs,
         max_sol_pairs,
+        nmin: parsed_row.nmin,
+        nmax: parsed_row.nmax,
+        all: None,
+        certify: false,
     })
 }
 
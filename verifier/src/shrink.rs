@@ -0,0 +1,59 @@
+//! shrinking failing inputs given in the ranking string syntax
+//!
+//! see [`lib::shrink`] for how. at the end, the tokens are renamed to `1..n` in
+//! the order they appear in a.
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use lib::def::*;
+use lib::shrink::shrink_orders;
+
+/// shrink the input `a`,`b` (in the ranking string syntax) for as long as
+/// `still_fails` holds, and return the smallest input it got to. `still_fails`
+/// should hold for the input itself.
+///
+/// ```
+/// # use verifier::shrink::shrink;
+/// // fails whenever there are ties in a, and b isn't one tie group
+/// let fails = |a: &str, b: &str| Ok(a.contains('(') && !b.starts_with('('));
+/// let (a, b) = shrink("q (w x y) z", "(q w) z y x", fails).unwrap();
+/// assert_eq!((a.as_str(), b.as_str()), ("(1 2)", "2 1"));
+/// ```
+pub fn shrink<F>(a: &str, b: &str, mut still_fails: F) -> Result<(String, String)>
+where
+    F: FnMut(&str, &str) -> Result<bool>,
+{
+    let mut map = BTreeMap::new();
+    let rank_a = partial_from_string(a, &mut map)?;
+    let rank_b = partial_from_string(b, &mut map)?;
+    let rmap = map
+        .into_iter()
+        .map(|(token, e)| (e, token))
+        .collect::<BTreeMap<Element, String>>();
+
+    let (rank_a, rank_b) = shrink_orders(&rank_a, &rank_b, |ca, cb| {
+        still_fails(
+            &partial_to_repl_string(ca, &rmap),
+            &partial_to_repl_string(cb, &rmap),
+        )
+    })?;
+
+    // renaming the tokens doesn't change how the input is parsed, but check
+    let renamed = rank_a
+        .concat()
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| (e, (i + 1).to_string()))
+        .collect::<BTreeMap<Element, String>>();
+    let print = |names: &BTreeMap<Element, String>| {
+        (
+            partial_to_repl_string(&rank_a, names),
+            partial_to_repl_string(&rank_b, names),
+        )
+    };
+    let (sa, sb) = print(&renamed);
+    if still_fails(&sa, &sb)? {
+        return Ok((sa, sb));
+    }
+    Ok(print(&rmap))
+}
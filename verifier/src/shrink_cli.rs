@@ -0,0 +1,92 @@
+//! # Shrinker
+//! take an input that `exec` fails on (compared to the solutions of the
+//! reference solver `reference`, e.g. `bf-cli`), and shrink it to the smallest
+//! input it can find that `exec` still fails on the same way (see
+//! [`verifier::shrink`]). every step runs both solvers again.
+//!
+//! exit:
+//! - code 0: the input was shrunk, and printed
+//! - anything else: the input didn't fail to begin with, or something broke
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::bail;
+use clap::Parser;
+use clap_derive::Parser;
+use lib::Limits;
+use lib::RunStatus;
+use lib::run_solver_on;
+use verifier::FailType;
+use verifier::TestCase;
+use verifier::TestResult;
+use verifier::shrink::shrink;
+use verifier::verify::parse_algo_sol;
+use verifier::verify::verify_result;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    pub exec: PathBuf,
+    pub reference: PathBuf,
+    pub a: String,
+    pub b: String,
+    #[command(flatten)]
+    pub limits: Limits,
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+
+    let Some(original) = failure(&args, &args.a, &args.b)? else {
+        bail!("{} doesn't fail on the input", args.exec.display());
+    };
+    println!("reason: {original:?}");
+    let kind = std::mem::discriminant(&original);
+
+    let mut runs = 0;
+    let (a, b) = shrink(&args.a, &args.b, |a, b| {
+        runs += 1;
+        Ok(failure(&args, a, b)?.is_some_and(|f| std::mem::discriminant(&f) == kind))
+    })?;
+
+    println!("shrunk in {runs} step(s) to:");
+    println!("{a}");
+    println!("{b}");
+    Ok(())
+}
+
+/// how `exec` fails on the input `a`,`b`, if it does. inputs the reference
+/// can't solve (within the limits), and runs of `exec` that error out, don't
+/// count as failing.
+fn failure(args: &Cli, a: &str, b: &str) -> Result<Option<FailType>> {
+    let inp = TestCase::unsolved(a, b);
+    let (status, _, _) = run_solver_on(&args.reference, inp.clone(), args.limits)?;
+    let RunStatus::Done(output) = status else {
+        return Ok(None);
+    };
+    let Some(sol) = parse_algo_sol(output)? else {
+        return Ok(None);
+    };
+    let (Some(tmin), Some(tmax)) = (sol.tmin, sol.tmax) else {
+        return Ok(None);
+    };
+    let case = TestCase {
+        tmin,
+        tmax,
+        min_sol_pairs: sol.minp,
+        max_sol_pairs: sol.maxp,
+        nmin: sol.nmin,
+        nmax: sol.nmax,
+        ..inp
+    };
+
+    // a candidate `exec` rejects outright fails some other way, not this one
+    let Ok((status, dt, _)) = run_solver_on(&args.exec, case.clone(), args.limits) else {
+        return Ok(None);
+    };
+    match verify_result(Ok(((status, dt), case)))?.0 {
+        TestResult::Fail(_, f) => Ok(Some(f)),
+        _ => Ok(None),
+    }
+}
@@ -5,10 +5,14 @@ use anyhow::anyhow;
 use anyhow::bail;
 use lib::AlgoOut;
 use lib::PRECISION;
+use lib::RunStatus;
+use lib::weights::WeightFn;
 
 use crate::FailType;
 use crate::TestCase;
 use crate::TestResult;
+use crate::certificate::check_certificate;
+use crate::consistency::inconsistency;
 
 pub fn parse_algo_sol(output: String) -> Result<Option<AlgoOut>> {
     let mut algo_sol = AlgoOut::default();
@@ -40,6 +44,30 @@ pub fn parse_algo_sol(output: String) -> Result<Option<AlgoOut>> {
                         .map_err(|e| anyhow!("failed to parse tau max: {value:?} ({e})"))?,
                 )
             }
+            "nmin" => {
+                algo_sol.nmin =
+                    Some(value.parse::<u128>().map_err(|e| {
+                        anyhow!("failed to parse count min: {value:?} ({e})")
+                    })?)
+            }
+            "nmax" => {
+                algo_sol.nmax =
+                    Some(value.parse::<u128>().map_err(|e| {
+                        anyhow!("failed to parse count max: {value:?} ({e})")
+                    })?)
+            }
+            "cmin" => {
+                algo_sol.cmin = Some(
+                    parse_pairs(value)
+                        .map_err(|e| anyhow!("failed to parse certificate min: {e}"))?,
+                )
+            }
+            "cmax" => {
+                algo_sol.cmax = Some(
+                    parse_pairs(value)
+                        .map_err(|e| anyhow!("failed to parse certificate max: {e}"))?,
+                )
+            }
             "minp" => {
                 let parsed = value.trim().split('/').collect::<Vec<_>>();
                 if parsed.len() != 2 {
@@ -64,10 +92,30 @@ pub fn parse_algo_sol(output: String) -> Result<Option<AlgoOut>> {
     Ok(Some(algo_sol))
 }
 
+/// the pairs of a certificate, `x y,x z` (or nothing, for no pairs)
+fn parse_pairs(value: &str) -> Result<Vec<(String, String)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(
+            |pair| match pair.split_whitespace().collect::<Vec<_>>()[..] {
+                [x, y] => Ok((x.to_string(), y.to_string())),
+                _ => Err(anyhow!("{pair:?} isn't a pair")),
+            },
+        )
+        .collect()
+}
+
 pub fn verify_result(
-    case: Result<((String, Duration), TestCase)>,
+    case: Result<((RunStatus, Duration), TestCase)>,
 ) -> Result<(TestResult, Duration)> {
-    let ((output, dt), input) = case?;
+    let ((status, dt), input) = case?;
+    let output = match status {
+        RunStatus::Done(output) => output,
+        RunStatus::Timeout => return Ok((TestResult::Timeout, dt)),
+        RunStatus::ResourceExceeded => return Ok((TestResult::ResourceExceeded, dt)),
+    };
     let algo_sol = if let Some(x) = parse_algo_sol(output)? {
         x
     } else {
@@ -81,16 +129,40 @@ pub fn verify_result(
         return Ok((TestResult::Empty(input), dt));
     }
 
+    if let Some(fail) = certificate_failure(&input, &algo_sol) {
+        return Ok((TestResult::Fail((input, algo_sol), fail), dt));
+    }
+
+    // the reference lists only so many arbitrations: if it counted more, one
+    // that isn't listed can still be optimal
+    let min_listed = input.min_sol_pairs.len() as u128;
+    let max_listed = input.max_sol_pairs.len() as u128;
+    let min_complete = input.nmin.is_none_or(|n| n == min_listed);
+    let max_complete = input.nmax.is_none_or(|n| n == max_listed);
+
     for (mina, minb) in &algo_sol.minp {
-        if !input
-            .min_sol_pairs
-            .iter()
-            .any(|p| p.0.eq(mina) && p.1.eq(minb))
+        if min_complete
+            && !input
+                .min_sol_pairs
+                .iter()
+                .any(|p| p.0.eq(mina) && p.1.eq(minb))
         {
             return Ok((TestResult::Fail((input, algo_sol), FailType::MinP), dt));
         }
     }
 
+    // if the algorithm counted its solutions, the listed ones are all of them
+    // exactly when the counts agree, so this makes the comparison complete.
+    let expected = input.nmin.unwrap_or(min_listed);
+    if let Some(n) = algo_sol.nmin
+        && n != expected
+    {
+        return Ok((
+            TestResult::Fail((input, algo_sol), FailType::MinCount(n, expected)),
+            dt,
+        ));
+    }
+
     if algo_sol
         .tmin
         .is_some_and(|tmin| (tmin - input.tmin).abs() > PRECISION)
@@ -103,15 +175,26 @@ pub fn verify_result(
     }
 
     for (maxa, maxb) in &algo_sol.maxp {
-        if !input
-            .max_sol_pairs
-            .iter()
-            .any(|p| p.0.eq(maxa) && p.1.eq(maxb))
+        if max_complete
+            && !input
+                .max_sol_pairs
+                .iter()
+                .any(|p| p.0.eq(maxa) && p.1.eq(maxb))
         {
             return Ok((TestResult::Fail((input, algo_sol), FailType::MaxP), dt));
         }
     }
 
+    let expected = input.nmax.unwrap_or(max_listed);
+    if let Some(n) = algo_sol.nmax
+        && n != expected
+    {
+        return Ok((
+            TestResult::Fail((input, algo_sol), FailType::MaxCount(n, expected)),
+            dt,
+        ));
+    }
+
     if algo_sol
         .tmax
         .is_some_and(|tmax| (tmax - input.tmax).abs() > PRECISION)
@@ -129,3 +212,73 @@ pub fn verify_result(
         Ok((TestResult::Pass, dt))
     }
 }
+
+/// like [`verify_result`], for cases without a reference solution: only checks
+/// that the output is consistent with the input (see
+/// [`crate::consistency`]) under the weight `w`, and its certificates if any.
+pub fn verify_consistency(
+    case: Result<((RunStatus, Duration), TestCase)>,
+    w: WeightFn,
+) -> Result<(TestResult, Duration)> {
+    let ((status, dt), input) = case?;
+    let output = match status {
+        RunStatus::Done(output) => output,
+        RunStatus::Timeout => return Ok((TestResult::Timeout, dt)),
+        RunStatus::ResourceExceeded => return Ok((TestResult::ResourceExceeded, dt)),
+    };
+    let algo_sol = if let Some(x) = parse_algo_sol(output)? {
+        x
+    } else {
+        return Ok((TestResult::Skipped, dt));
+    };
+    let min_sol_exists = algo_sol.tmin.is_some() && !algo_sol.minp.is_empty();
+    let max_sol_exists = algo_sol.tmax.is_some() && !algo_sol.maxp.is_empty();
+    if !(min_sol_exists || max_sol_exists) {
+        return Ok((TestResult::Empty(input), dt));
+    }
+
+    let fail = certificate_failure(&input, &algo_sol)
+        .or_else(|| inconsistency(&input.a, &input.b, &algo_sol, w));
+    if let Some(fail) = fail {
+        return Ok((TestResult::Fail((input, algo_sol), fail), dt));
+    }
+
+    if min_sol_exists && max_sol_exists {
+        Ok((TestResult::Complete, dt))
+    } else {
+        Ok((TestResult::Pass, dt))
+    }
+}
+
+/// check the certificates of optimality the algorithm gave (for its first
+/// solutions). if the input asked for them, every bound given must have one.
+pub fn certificate_failure(input: &TestCase, algo_sol: &AlgoOut) -> Option<FailType> {
+    let (a, b) = (&input.a, &input.b);
+    if let (Some(t), Some((sa, sb))) = (algo_sol.tmin, algo_sol.minp.first()) {
+        match &algo_sol.cmin {
+            Some(forced) => {
+                if let Err(e) = check_certificate(a, b, (sa, sb), forced, t, true) {
+                    return Some(FailType::MinCert(e.to_string()));
+                }
+            }
+            None if input.certify => {
+                return Some(FailType::MinCert("no certificate".to_string()));
+            }
+            None => {}
+        }
+    }
+    if let (Some(t), Some((sa, sb))) = (algo_sol.tmax, algo_sol.maxp.first()) {
+        match &algo_sol.cmax {
+            Some(forced) => {
+                if let Err(e) = check_certificate(a, b, (sa, sb), forced, t, false) {
+                    return Some(FailType::MaxCert(e.to_string()));
+                }
+            }
+            None if input.certify => {
+                return Some(FailType::MaxCert("no certificate".to_string()));
+            }
+            None => {}
+        }
+    }
+    None
+}
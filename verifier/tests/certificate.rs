@@ -0,0 +1,77 @@
+//! a solver whose certificate is wrong, or missing, fails the verifier, but
+//! only once the verifier asks it for one.
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use lib::Limits;
+use lib::run_solver_on;
+use verifier::FailType;
+use verifier::TestCase;
+use verifier::TestResult;
+use verifier::verify::verify_result;
+
+/// the right bounds of `(x y)`,`x y`, with a certificate (when asked) that
+/// claims a pair goes against the minimum, when none does.
+const WRONG: &str = r#"#!/bin/sh
+echo "tmin:-1.0"
+echo "minp:y x/x y"
+echo "tmax:1.0"
+echo "maxp:x y/x y"
+case " $* " in
+    *" --certify "*) echo "cmin:y x" && echo "cmax:" ;;
+esac
+"#;
+
+/// the right bounds of `(x y)`,`x y`, and never a certificate
+const MISSING: &str = r#"#!/bin/sh
+echo "tmin:-1.0"
+echo "minp:y x/x y"
+echo "tmax:1.0"
+echo "maxp:x y/x y"
+"#;
+
+fn solver(name: &str, script: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn verify(solver: &PathBuf, certify: bool) -> TestResult {
+    let case = TestCase {
+        tmin: -1.0,
+        tmax: 1.0,
+        min_sol_pairs: vec![("y x".to_string(), "x y".to_string())],
+        max_sol_pairs: vec![("x y".to_string(), "x y".to_string())],
+        certify,
+        ..TestCase::unsolved("(x y)", "x y")
+    };
+    let (status, dt, _) = run_solver_on(solver, case.clone(), Limits::default()).unwrap();
+    verify_result(Ok(((status, dt), case))).unwrap().0
+}
+
+#[test]
+fn wrong_certificate() {
+    let solver = solver("wrong-certificate", WRONG);
+    assert!(matches!(verify(&solver, false), TestResult::Complete));
+    let result = verify(&solver, true);
+    std::fs::remove_file(&solver).unwrap();
+    match result {
+        TestResult::Fail(_, FailType::MinCert(why)) => {
+            assert!(why.contains("doesn't go against"), "{why}")
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn missing_certificate() {
+    let solver = solver("missing-certificate", MISSING);
+    assert!(matches!(verify(&solver, false), TestResult::Complete));
+    let result = verify(&solver, true);
+    std::fs::remove_file(&solver).unwrap();
+    match result {
+        TestResult::Fail(_, FailType::MinCert(why)) => assert_eq!(why, "no certificate"),
+        other => panic!("{other:?}"),
+    }
+}